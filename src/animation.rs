@@ -0,0 +1,86 @@
+use crate::Position;
+
+pub const MAX_ANIMATIONS: usize = 8;
+pub const FLASH_TICKS: usize = 12;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Effect {
+    /// Stars around a head that has just eaten.
+    Sparkle,
+    /// Food appearing where it was just placed.
+    FadeIn,
+    /// A player's snake flashing and then crumbling away from its head.
+    Death(usize)
+}
+
+impl Effect {
+    pub fn ticks(&self) -> usize {
+        match self {
+            Effect::Sparkle => 6,
+            Effect::FadeIn => 9,
+            Effect::Death(_) => 3 * FLASH_TICKS
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Animation<const WIDTH: usize, const HEIGHT: usize> {
+    pub effect: Effect,
+    pub pos: Position<WIDTH,HEIGHT>,
+    start: usize
+}
+
+impl <const WIDTH: usize, const HEIGHT: usize> Animation<WIDTH,HEIGHT> {
+    /// Ticks since the animation started.
+    pub fn frame(&self, now: usize) -> usize {
+        now.wrapping_sub(self.start)
+    }
+
+    pub fn is_done(&self, now: usize) -> bool {
+        self.frame(now) >= self.effect.ticks()
+    }
+}
+
+/// Animations run on `tick` rather than on game steps, so they keep their
+/// pace at every speed and carry on after the game is over.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Animations<const WIDTH: usize, const HEIGHT: usize> {
+    slots: [Option<Animation<WIDTH,HEIGHT>>; MAX_ANIMATIONS]
+}
+
+impl <const WIDTH: usize, const HEIGHT: usize> Default for Animations<WIDTH,HEIGHT> {
+    fn default() -> Self {
+        Animations { slots: [None; MAX_ANIMATIONS] }
+    }
+}
+
+impl <const WIDTH: usize, const HEIGHT: usize> Animations<WIDTH,HEIGHT> {
+    /// Takes a free slot, or the oldest animation's slot when all are busy.
+    pub fn start(&mut self, effect: Effect, pos: Position<WIDTH,HEIGHT>, now: usize) {
+        let animation = Animation { effect, pos, start: now };
+        let slot = self.slots.iter().position(|slot| slot.is_none()).unwrap_or_else(|| {
+            (0..MAX_ANIMATIONS).max_by_key(|i| self.slots[*i].map_or(0, |a| a.frame(now))).unwrap_or(0)
+        });
+        self.slots[slot] = Some(animation);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Animation<WIDTH,HEIGHT>> {
+        self.slots.iter().flatten()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(|slot| slot.is_none())
+    }
+
+    pub fn remove_done(&mut self, now: usize) {
+        for slot in self.slots.iter_mut() {
+            if slot.is_some_and(|a| a.is_done(now)) {
+                *slot = None;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.slots = [None; MAX_ANIMATIONS];
+    }
+}
@@ -0,0 +1,93 @@
+use pluggable_interrupt_os::vga_buffer::{Color, ColorCode};
+
+pub type ScreenCell = (char, ColorCode);
+
+/// Something a frame can be shown on, one text cell at a time.
+pub trait Screen {
+    fn write_cell(&mut self, c: char, col: usize, row: usize, color: ColorCode);
+}
+
+/// Off-screen copy of the text buffer. The game draws into one frame and
+/// `blit` copies only the cells that differ from the previously shown frame.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Frame<const WIDTH: usize, const HEIGHT: usize> {
+    cells: [[ScreenCell; WIDTH]; HEIGHT]
+}
+
+impl <const WIDTH: usize, const HEIGHT: usize> Frame<WIDTH, HEIGHT> {
+    pub fn new(fill: ScreenCell) -> Self {
+        Frame { cells: [[fill; WIDTH]; HEIGHT] }
+    }
+
+    pub fn get(&self, col: usize, row: usize) -> ScreenCell {
+        self.cells[row][col]
+    }
+
+    pub fn plot(&mut self, c: char, col: usize, row: usize, color: ColorCode) {
+        if col < WIDTH && row < HEIGHT {
+            self.cells[row][col] = (c, color);
+        }
+    }
+
+    /// Returns the column just past the end of `s`.
+    pub fn plot_str(&mut self, s: &str, col: usize, row: usize, color: ColorCode) -> usize {
+        let mut end = col;
+        for c in s.chars() {
+            self.plot(c, end, row, color);
+            end += 1;
+        }
+        end
+    }
+
+    /// Returns the column just past the last digit.
+    pub fn plot_num(&mut self, num: isize, col: usize, row: usize, color: ColorCode) -> usize {
+        let mut col = col;
+        if num < 0 {
+            self.plot('-', col, row, color);
+            col += 1;
+        }
+        let mut digits = [0u8; 20];
+        let mut len = 0;
+        let mut value = num.unsigned_abs();
+        loop {
+            digits[len] = b'0' + (value % 10) as u8;
+            len += 1;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        for digit in digits[..len].iter().rev() {
+            self.plot(*digit as char, col, row, color);
+            col += 1;
+        }
+        col
+    }
+
+    pub fn clear_row(&mut self, row: usize, background: Color) {
+        for col in 0..WIDTH {
+            self.plot(' ', col, row, ColorCode::new(background, background));
+        }
+    }
+
+    pub fn differences<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = (usize, usize)> + 'a {
+        (0..HEIGHT).flat_map(move |row| (0..WIDTH).map(move |col| (col, row)))
+            .filter(move |(col, row)| self.cells[*row][*col] != other.cells[*row][*col])
+    }
+
+    /// Writes every cell in the top `rows` rows that differs from `shown` to
+    /// `screen` and records it in `shown`. Returns the number of cells written.
+    pub fn blit(&self, shown: &mut Self, rows: usize, screen: &mut impl Screen) -> usize {
+        let mut written = 0;
+        for (row, (cells, shown_cells)) in self.cells.iter().zip(shown.cells.iter_mut()).enumerate().take(rows) {
+            for (col, (cell, shown_cell)) in cells.iter().zip(shown_cells.iter_mut()).enumerate() {
+                if cell != shown_cell {
+                    screen.write_cell(cell.0, col, row, cell.1);
+                    *shown_cell = *cell;
+                    written += 1;
+                }
+            }
+        }
+        written
+    }
+}
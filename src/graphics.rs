@@ -0,0 +1,328 @@
+use pluggable_interrupt_os::vga_buffer::ColorCode;
+
+use crate::frame::Screen;
+use crate::text_mode::{attribute, inb, outb, read_register, write_register, CRTC, GRAPHICS, SEQUENCER};
+use crate::Dir;
+
+pub const PIXEL_WIDTH: usize = 320;
+pub const PIXEL_HEIGHT: usize = 200;
+pub const TILE_WIDTH: usize = 4;
+pub const TILE_HEIGHT: usize = 8;
+const PIXELS: *mut u8 = 0xa0000 as *mut u8;
+const TILE_BASE: u32 = 0xe000;
+const MISC_OUTPUT: u16 = 0x3c2;
+const ATTRIBUTE: u16 = 0x3c0;
+const INPUT_STATUS: u16 = 0x3da;
+const DAC_INDEX: u16 = 0x3c8;
+const DAC_DATA: u16 = 0x3c9;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Renderer {
+    Text,
+    Pixels
+}
+
+/// Board objects that the pixel renderer draws as sprites. They travel
+/// through the frame as private-use code points, which never occur in text.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Tile {
+    Head(Dir),
+    /// Bit `1 << dir` is set for each neighbouring segment.
+    Body(u8),
+    Food,
+    Wall
+}
+
+impl Tile {
+    pub fn glyph(&self) -> char {
+        let offset = match self {
+            Tile::Head(dir) => *dir as u32,
+            Tile::Body(links) => 0x10 + (*links & 0x0f) as u32,
+            Tile::Food => 0x20,
+            Tile::Wall => 0x21
+        };
+        char::from_u32(TILE_BASE + offset).unwrap()
+    }
+
+    pub fn from_glyph(c: char) -> Option<Tile> {
+        match (c as u32).checked_sub(TILE_BASE)? {
+            offset @ 0..=3 => Some(Tile::Head([Dir::N, Dir::S, Dir::E, Dir::W][offset as usize])),
+            offset @ 0x10..=0x1f => Some(Tile::Body((offset - 0x10) as u8)),
+            0x20 => Some(Tile::Food),
+            0x21 => Some(Tile::Wall),
+            _ => None
+        }
+    }
+
+    /// Code points of the glyphs that `Font::with_snake_glyphs` replaces. All
+    /// lie in 0xC0-0xDF, where the VGA repeats a glyph's eighth pixel column
+    /// into the ninth so that neighbouring cells join up.
+    pub const fn text_glyph(&self) -> char {
+        match self {
+            Tile::Head(Dir::N) => '\u{C6}',
+            Tile::Head(Dir::S) => '\u{C7}',
+            Tile::Head(Dir::E) => '\u{CB}',
+            Tile::Head(Dir::W) => '\u{CA}',
+            Tile::Body(0b0011) => '\u{D0}',
+            Tile::Body(0b1100) => '\u{D1}',
+            Tile::Body(0b0110) => '\u{D2}',
+            Tile::Body(0b1010) => '\u{D3}',
+            Tile::Body(0b0101) => '\u{D4}',
+            Tile::Body(0b1001) => '\u{D5}',
+            Tile::Body(_) => '\u{D6}',
+            Tile::Wall => '\u{D7}',
+            Tile::Food => '\u{D8}'
+        }
+    }
+
+    /// Recovers the links of a single-line body glyph from `Dir::body_glyph`.
+    pub fn body(glyph: char) -> Tile {
+        let links = match glyph {
+            '\u{B3}' => [Dir::N, Dir::S],
+            '\u{C4}' => [Dir::E, Dir::W],
+            '\u{DA}' => [Dir::S, Dir::E],
+            '\u{BF}' => [Dir::S, Dir::W],
+            '\u{C0}' => [Dir::N, Dir::E],
+            '\u{D9}' => [Dir::N, Dir::W],
+            _ => return Tile::Body(0)
+        };
+        Tile::Body(links.iter().fold(0, |mask, dir| mask | 1 << *dir as u8))
+    }
+
+    /// Rows of four pixels, most significant of the low four bits leftmost.
+    fn sprite(&self) -> [u8; TILE_HEIGHT] {
+        match self {
+            Tile::Head(Dir::N) => [0b0000, 0b0110, 0b0110, 0b1111, 0b1001, 0b1111, 0b1111, 0b0110],
+            Tile::Head(Dir::S) => [0b0110, 0b1111, 0b1111, 0b1001, 0b1111, 0b0110, 0b0110, 0b0000],
+            Tile::Head(Dir::E) => [0b0000, 0b1000, 0b1110, 0b1101, 0b1111, 0b1110, 0b1000, 0b0000],
+            Tile::Head(Dir::W) => [0b0000, 0b0001, 0b0111, 0b1011, 0b1111, 0b0111, 0b0001, 0b0000],
+            Tile::Body(links) => {
+                let linked = |dir: Dir| links & (1 << dir as u8) != 0;
+                let vertical = |dir: Dir| if linked(dir) { 0b0110 } else { 0b0000 };
+                let middle = 0b0110 | if linked(Dir::W) { 0b1000 } else { 0 } | if linked(Dir::E) { 0b0001 } else { 0 };
+                [vertical(Dir::N), vertical(Dir::N), 0b0110, middle, middle, 0b0110, vertical(Dir::S), vertical(Dir::S)]
+            }
+            Tile::Food => [0b0010, 0b0100, 0b0110, 0b1111, 0b1111, 0b1111, 0b0110, 0b0000],
+            Tile::Wall => [0b1110, 0b1110, 0b1110, 0b0000, 0b1011, 0b1011, 0b1011, 0b0000]
+        }
+    }
+}
+
+/// Draws each text cell as a 4x8 tile of the 320x200 mode 13h screen: tiles
+/// as sprites and everything else in a 3x5 bitmap font.
+pub struct PixelScreen;
+
+impl Screen for PixelScreen {
+    fn write_cell(&mut self, c: char, col: usize, row: usize, color: ColorCode) {
+        if col >= PIXEL_WIDTH / TILE_WIDTH || row >= PIXEL_HEIGHT / TILE_HEIGHT {
+            return;
+        }
+        let sprite = match Tile::from_glyph(c) {
+            Some(tile) => tile.sprite(),
+            None => {
+                let glyph = font_glyph(c);
+                let mut sprite = [0; TILE_HEIGHT];
+                sprite[1..6].copy_from_slice(&glyph);
+                sprite.iter_mut().for_each(|bits| *bits <<= 1);
+                sprite
+            }
+        };
+        let attribute = attribute(color);
+        let (foreground, background) = (attribute & 0x0f, attribute >> 4);
+        for (y, bits) in sprite.iter().enumerate() {
+            for x in 0..TILE_WIDTH {
+                let lit = bits & (0b1000 >> x) != 0;
+                let offset = (row * TILE_HEIGHT + y) * PIXEL_WIDTH + col * TILE_WIDTH + x;
+                unsafe { PIXELS.add(offset).write_volatile(if lit { foreground } else { background }) };
+            }
+        }
+    }
+}
+
+/// Programs the VGA registers for 320x200 with 256 colours, then loads the
+/// sixteen text-mode colours into the first DAC entries so that `Color`
+/// values work as pixel values.
+pub fn set_mode_13h() {
+    const SEQUENCER_VALUES: [u8; 5] = [0x03, 0x01, 0x0f, 0x00, 0x0e];
+    const CRTC_VALUES: [u8; 25] = [
+        0x5f, 0x4f, 0x50, 0x82, 0x54, 0x80, 0xbf, 0x1f, 0x00, 0x41, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x9c, 0x0e, 0x8f, 0x28, 0x40, 0x96, 0xb9, 0xa3, 0xff
+    ];
+    const GRAPHICS_VALUES: [u8; 9] = [0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x05, 0x0f, 0xff];
+    const ATTRIBUTE_VALUES: [u8; 21] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+        0x41, 0x00, 0x0f, 0x00, 0x00
+    ];
+    const PALETTE: [(u8, u8, u8); 16] = [
+        (0, 0, 0), (0, 0, 42), (0, 42, 0), (0, 42, 42), (42, 0, 0), (42, 0, 42), (42, 21, 0), (42, 42, 42),
+        (21, 21, 21), (21, 21, 63), (21, 63, 21), (21, 63, 63), (63, 21, 21), (63, 21, 63), (63, 63, 21), (63, 63, 63)
+    ];
+    unsafe {
+        outb(MISC_OUTPUT, 0x63);
+        for (i, value) in SEQUENCER_VALUES.iter().enumerate() {
+            write_register(SEQUENCER, i as u8, *value);
+        }
+        // Registers 0 through 7 are write-protected until bit 7 of register 0x11 is cleared.
+        let vertical_retrace_end = read_register(CRTC, 0x11);
+        write_register(CRTC, 0x11, vertical_retrace_end & 0x7f);
+        for (i, value) in CRTC_VALUES.iter().enumerate() {
+            write_register(CRTC, i as u8, *value);
+        }
+        for (i, value) in GRAPHICS_VALUES.iter().enumerate() {
+            write_register(GRAPHICS, i as u8, *value);
+        }
+        for (i, value) in ATTRIBUTE_VALUES.iter().enumerate() {
+            inb(INPUT_STATUS);
+            outb(ATTRIBUTE, i as u8);
+            outb(ATTRIBUTE, *value);
+        }
+        inb(INPUT_STATUS);
+        outb(ATTRIBUTE, 0x20);
+        outb(DAC_INDEX, 0);
+        for (red, green, blue) in PALETTE {
+            outb(DAC_DATA, red);
+            outb(DAC_DATA, green);
+            outb(DAC_DATA, blue);
+        }
+    }
+}
+
+/// Covers printable ASCII. The CP437 lines and blocks that can appear off
+/// the board are drawn as the nearest ASCII, and anything else becomes `?`.
+pub(crate) fn font_glyph(c: char) -> [u8; 5] {
+    let c = match c {
+        '\u{BA}' => return [0b101; 5],
+        '\u{B3}' => '|',
+        '\u{C4}' => '-',
+        '\u{CD}' => '=',
+        '\u{B0}'..='\u{B2}' | '\u{DB}'..='\u{DF}' => '#',
+        '\u{B4}'..='\u{DA}' => '+',
+        '\u{F9}' | '\u{FA}' => '.',
+        '\u{FE}' => 'o',
+        c => c
+    };
+    match c {
+        ' '..='~' => FONT[c as usize - ' ' as usize],
+        _ => FONT['?' as usize - ' ' as usize]
+    }
+}
+
+const FONT: [[u8; 5]; 95] = [
+    [0b000, 0b000, 0b000, 0b000, 0b000], // ' '
+    [0b010, 0b010, 0b010, 0b000, 0b010], // !
+    [0b101, 0b101, 0b000, 0b000, 0b000], // "
+    [0b101, 0b111, 0b101, 0b111, 0b101], // #
+    [0b011, 0b110, 0b010, 0b011, 0b110], // $
+    [0b101, 0b001, 0b010, 0b100, 0b101], // %
+    [0b010, 0b101, 0b010, 0b101, 0b011], // &
+    [0b010, 0b010, 0b000, 0b000, 0b000], // '
+    [0b001, 0b010, 0b010, 0b010, 0b001], // (
+    [0b100, 0b010, 0b010, 0b010, 0b100], // )
+    [0b000, 0b101, 0b010, 0b101, 0b000], // *
+    [0b000, 0b010, 0b111, 0b010, 0b000], // +
+    [0b000, 0b000, 0b000, 0b010, 0b100], // ,
+    [0b000, 0b000, 0b111, 0b000, 0b000], // -
+    [0b000, 0b000, 0b000, 0b000, 0b010], // .
+    [0b001, 0b001, 0b010, 0b100, 0b100], // /
+    [0b111, 0b101, 0b101, 0b101, 0b111], // 0
+    [0b010, 0b110, 0b010, 0b010, 0b111], // 1
+    [0b111, 0b001, 0b111, 0b100, 0b111], // 2
+    [0b111, 0b001, 0b111, 0b001, 0b111], // 3
+    [0b101, 0b101, 0b111, 0b001, 0b001], // 4
+    [0b111, 0b100, 0b111, 0b001, 0b111], // 5
+    [0b111, 0b100, 0b111, 0b101, 0b111], // 6
+    [0b111, 0b001, 0b001, 0b010, 0b010], // 7
+    [0b111, 0b101, 0b111, 0b101, 0b111], // 8
+    [0b111, 0b101, 0b111, 0b001, 0b111], // 9
+    [0b000, 0b010, 0b000, 0b010, 0b000], // :
+    [0b000, 0b010, 0b000, 0b010, 0b100], // ;
+    [0b001, 0b010, 0b100, 0b010, 0b001], // <
+    [0b000, 0b111, 0b000, 0b111, 0b000], // =
+    [0b100, 0b010, 0b001, 0b010, 0b100], // >
+    [0b111, 0b001, 0b010, 0b000, 0b010], // ?
+    [0b010, 0b101, 0b111, 0b100, 0b011], // @
+    [0b010, 0b101, 0b111, 0b101, 0b101], // A
+    [0b110, 0b101, 0b110, 0b101, 0b110], // B
+    [0b011, 0b100, 0b100, 0b100, 0b011], // C
+    [0b110, 0b101, 0b101, 0b101, 0b110], // D
+    [0b111, 0b100, 0b110, 0b100, 0b111], // E
+    [0b111, 0b100, 0b110, 0b100, 0b100], // F
+    [0b011, 0b100, 0b101, 0b101, 0b011], // G
+    [0b101, 0b101, 0b111, 0b101, 0b101], // H
+    [0b111, 0b010, 0b010, 0b010, 0b111], // I
+    [0b001, 0b001, 0b001, 0b101, 0b010], // J
+    [0b101, 0b101, 0b110, 0b101, 0b101], // K
+    [0b100, 0b100, 0b100, 0b100, 0b111], // L
+    [0b101, 0b111, 0b101, 0b101, 0b101], // M
+    [0b110, 0b101, 0b101, 0b101, 0b101], // N
+    [0b010, 0b101, 0b101, 0b101, 0b010], // O
+    [0b110, 0b101, 0b110, 0b100, 0b100], // P
+    [0b010, 0b101, 0b101, 0b110, 0b011], // Q
+    [0b110, 0b101, 0b110, 0b101, 0b101], // R
+    [0b011, 0b100, 0b010, 0b001, 0b110], // S
+    [0b111, 0b010, 0b010, 0b010, 0b010], // T
+    [0b101, 0b101, 0b101, 0b101, 0b111], // U
+    [0b101, 0b101, 0b101, 0b101, 0b010], // V
+    [0b101, 0b101, 0b101, 0b111, 0b101], // W
+    [0b101, 0b101, 0b010, 0b101, 0b101], // X
+    [0b101, 0b101, 0b010, 0b010, 0b010], // Y
+    [0b111, 0b001, 0b010, 0b100, 0b111], // Z
+    [0b110, 0b100, 0b100, 0b100, 0b110], // [
+    [0b100, 0b100, 0b010, 0b001, 0b001], // \
+    [0b011, 0b001, 0b001, 0b001, 0b011], // ]
+    [0b010, 0b101, 0b000, 0b000, 0b000], // ^
+    [0b000, 0b000, 0b000, 0b000, 0b111], // _
+    [0b100, 0b010, 0b000, 0b000, 0b000], // `
+    [0b000, 0b011, 0b101, 0b101, 0b011], // a
+    [0b100, 0b110, 0b101, 0b101, 0b110], // b
+    [0b000, 0b011, 0b100, 0b100, 0b011], // c
+    [0b001, 0b011, 0b101, 0b101, 0b011], // d
+    [0b000, 0b010, 0b111, 0b100, 0b011], // e
+    [0b001, 0b010, 0b111, 0b010, 0b010], // f
+    [0b000, 0b011, 0b101, 0b011, 0b110], // g
+    [0b100, 0b110, 0b101, 0b101, 0b101], // h
+    [0b010, 0b000, 0b010, 0b010, 0b010], // i
+    [0b001, 0b000, 0b001, 0b101, 0b010], // j
+    [0b100, 0b101, 0b110, 0b110, 0b101], // k
+    [0b110, 0b010, 0b010, 0b010, 0b111], // l
+    [0b000, 0b111, 0b111, 0b101, 0b101], // m
+    [0b000, 0b110, 0b101, 0b101, 0b101], // n
+    [0b000, 0b010, 0b101, 0b101, 0b010], // o
+    [0b000, 0b110, 0b101, 0b110, 0b100], // p
+    [0b000, 0b011, 0b101, 0b011, 0b001], // q
+    [0b000, 0b101, 0b110, 0b100, 0b100], // r
+    [0b000, 0b011, 0b110, 0b011, 0b110], // s
+    [0b010, 0b111, 0b010, 0b010, 0b001], // t
+    [0b000, 0b101, 0b101, 0b101, 0b011], // u
+    [0b000, 0b101, 0b101, 0b101, 0b010], // v
+    [0b000, 0b101, 0b101, 0b111, 0b111], // w
+    [0b000, 0b101, 0b010, 0b010, 0b101], // x
+    [0b000, 0b101, 0b101, 0b010, 0b100], // y
+    [0b000, 0b111, 0b011, 0b100, 0b111], // z
+    [0b011, 0b010, 0b110, 0b010, 0b011], // {
+    [0b010, 0b010, 0b010, 0b010, 0b010], // |
+    [0b110, 0b010, 0b011, 0b010, 0b110], // }
+    [0b000, 0b011, 0b110, 0b000, 0b000]  // ~
+];
+
+#[cfg(test)]
+mod tests {
+    use super::font_glyph;
+
+    #[test]
+    fn every_printable_character_has_its_own_glyph() {
+        let unknown = font_glyph('\u{1}');
+        for c in (' '..='~').filter(|c| *c != '?') {
+            assert_ne!(font_glyph(c), unknown, "{:?}", c);
+        }
+        assert_ne!(font_glyph('a'), font_glyph('A'));
+    }
+
+    #[test]
+    fn box_drawing_falls_back_to_ascii() {
+        assert_eq!(font_glyph('\u{BA}'), [0b101; 5]);
+        assert_eq!(font_glyph('\u{C4}'), font_glyph('-'));
+        assert_eq!(font_glyph('\u{DA}'), font_glyph('+'));
+        assert_eq!(font_glyph('\u{DB}'), font_glyph('#'));
+    }
+}
@@ -0,0 +1,130 @@
+use pc_keyboard::{DecodedKey, KeyCode};
+
+use crate::Dir;
+
+/// The order directions are listed in on the controls screen.
+pub const DIRS: [Dir; 4] = [Dir::N, Dir::S, Dir::W, Dir::E];
+pub const DIR_NAMES: [&str; 4] = ["Up", "Down", "Left", "Right"];
+
+/// A key as the decoder delivers it: a character, or a key that has none.
+/// Letters are stored in lower case so that Shift and Caps Lock don't matter.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Binding {
+    Char(char),
+    Key(KeyCode)
+}
+
+impl Binding {
+    pub fn of(key: DecodedKey) -> Binding {
+        match key {
+            DecodedKey::Unicode(c) => Binding::Char(c.to_ascii_lowercase()),
+            DecodedKey::RawKey(k) => Binding::Key(k)
+        }
+    }
+
+    /// Writes the key's name into `buf` when it is a printable character.
+    pub fn name<'a>(&self, buf: &'a mut [u8; 4]) -> &'a str {
+        match self {
+            Binding::Char(' ') => "Space",
+            Binding::Char(c) => c.to_ascii_uppercase().encode_utf8(buf),
+            Binding::Key(KeyCode::ArrowUp) => "Up arrow",
+            Binding::Key(KeyCode::ArrowDown) => "Down arrow",
+            Binding::Key(KeyCode::ArrowLeft) => "Left arrow",
+            Binding::Key(KeyCode::ArrowRight) => "Right arrow",
+            Binding::Key(KeyCode::Home) => "Home",
+            Binding::Key(KeyCode::End) => "End",
+            Binding::Key(KeyCode::PageUp) => "Page Up",
+            Binding::Key(KeyCode::PageDown) => "Page Down",
+            Binding::Key(KeyCode::Insert) => "Insert",
+            Binding::Key(KeyCode::Delete) => "Delete",
+            Binding::Key(_) => "Special"
+        }
+    }
+
+    /// Keys that the menus need for themselves, including the digits that
+    /// quick-start a mode from the title screen.
+    pub fn is_reserved(&self) -> bool {
+        matches!(self, Binding::Char('\n' | '\u{1b}' | '\t' | '0'..='9'))
+    }
+}
+
+/// One player's direction keys, in `DIRS` order.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Keymap {
+    pub bindings: [Binding; 4]
+}
+
+impl Keymap {
+    pub fn dir(&self, key: DecodedKey) -> Option<Dir> {
+        let pressed = Binding::of(key);
+        self.bindings.iter().position(|b| *b == pressed).map(|i| DIRS[i])
+    }
+}
+
+pub const PLAYER1_KEYS: Keymap = Keymap {
+    bindings: [Binding::Char('w'), Binding::Char('s'), Binding::Char('a'), Binding::Char('d')]
+};
+
+pub const PLAYER2_KEYS: Keymap = Keymap {
+    bindings: [Binding::Key(KeyCode::ArrowUp), Binding::Key(KeyCode::ArrowDown),
+               Binding::Key(KeyCode::ArrowLeft), Binding::Key(KeyCode::ArrowRight)]
+};
+
+/// Binds `key` to `dir` for `player`. If another slot already had the key,
+/// it takes over the slot's old binding so that no key does two things.
+pub fn rebind(keymaps: &mut [Keymap; 2], player: usize, dir: usize, key: Binding) {
+    let old = keymaps[player].bindings[dir];
+    for keymap in keymaps.iter_mut() {
+        for binding in keymap.bindings.iter_mut() {
+            if *binding == key {
+                *binding = old;
+            }
+        }
+    }
+    keymaps[player].bindings[dir] = key;
+}
+
+#[cfg(test)]
+mod tests {
+    use pc_keyboard::{DecodedKey, KeyCode};
+
+    use super::{rebind, Binding, PLAYER1_KEYS, PLAYER2_KEYS};
+    use crate::Dir;
+
+    #[test]
+    fn letters_match_in_either_case() {
+        assert_eq!(Binding::of(DecodedKey::Unicode('W')), Binding::Char('w'));
+        assert_eq!(PLAYER1_KEYS.dir(DecodedKey::Unicode('w')), Some(Dir::N));
+        assert_eq!(PLAYER1_KEYS.dir(DecodedKey::Unicode('D')), Some(Dir::E));
+        assert_eq!(PLAYER1_KEYS.dir(DecodedKey::Unicode('x')), None);
+    }
+
+    #[test]
+    fn rebinding_an_uppercase_letter_stores_it_lowercase() {
+        let mut keymaps = [PLAYER1_KEYS, PLAYER2_KEYS];
+        rebind(&mut keymaps, 0, 0, Binding::of(DecodedKey::Unicode('I')));
+        assert_eq!(keymaps[0].dir(DecodedKey::Unicode('i')), Some(Dir::N));
+        assert_eq!(keymaps[0].dir(DecodedKey::Unicode('I')), Some(Dir::N));
+    }
+
+    #[test]
+    fn rebinding_a_bound_key_swaps_the_two_slots() {
+        let mut keymaps = [PLAYER1_KEYS, PLAYER2_KEYS];
+        rebind(&mut keymaps, 0, 0, Binding::Char('s'));
+        assert_eq!(keymaps[0].bindings[0], Binding::Char('s'));
+        assert_eq!(keymaps[0].bindings[1], Binding::Char('w'));
+
+        rebind(&mut keymaps, 0, 2, Binding::Key(KeyCode::ArrowUp));
+        assert_eq!(keymaps[0].bindings[2], Binding::Key(KeyCode::ArrowUp));
+        assert_eq!(keymaps[1].bindings[0], Binding::Char('a'));
+    }
+
+    #[test]
+    fn menu_keys_are_reserved() {
+        for c in ['\n', '\u{1b}', '\t', '0', '1', '9'] {
+            assert!(Binding::Char(c).is_reserved());
+        }
+        assert!(!Binding::Char('w').is_reserved());
+        assert!(!Binding::Key(KeyCode::ArrowUp).is_reserved());
+    }
+}
@@ -0,0 +1,117 @@
+use pc_keyboard::DecodedKey;
+
+use crate::keymap::{Binding, Keymap, PLAYER1_KEYS};
+
+/// The operating system layer always decodes scancodes as a US keyboard, so
+/// other layouts are applied afterwards by translating the characters.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum KeyboardLayout {
+    Qwerty,
+    Azerty,
+    Dvorak
+}
+
+pub const LAYOUTS: [KeyboardLayout; 3] = [KeyboardLayout::Qwerty, KeyboardLayout::Azerty, KeyboardLayout::Dvorak];
+
+impl KeyboardLayout {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyboardLayout::Qwerty => "QWERTY",
+            KeyboardLayout::Azerty => "AZERTY",
+            KeyboardLayout::Dvorak => "Dvorak"
+        }
+    }
+
+    /// Turns what a US keyboard would have typed into what this layout
+    /// types with the same key. Keys without characters pass through.
+    pub fn translate(&self, key: DecodedKey) -> DecodedKey {
+        let table: &[(char, char, char, char)] = match self {
+            KeyboardLayout::Qwerty => &[],
+            KeyboardLayout::Azerty => &AZERTY,
+            KeyboardLayout::Dvorak => &DVORAK
+        };
+        match key {
+            DecodedKey::Unicode(c) => DecodedKey::Unicode(table.iter()
+                .find_map(|(lower, upper, to_lower, to_upper)| {
+                    if c == *lower { Some(*to_lower) } else if c == *upper { Some(*to_upper) } else { None }
+                })
+                .unwrap_or(c)),
+            raw => raw
+        }
+    }
+
+    /// Player one's default keys sit where W, A, S and D are on a US
+    /// keyboard, whatever those keys are labelled.
+    pub fn player1_keys(&self) -> Keymap {
+        let [up, down, left, right] = PLAYER1_KEYS.bindings.map(|binding| match binding {
+            Binding::Char(c) => self.translate(DecodedKey::Unicode(c)),
+            Binding::Key(k) => DecodedKey::RawKey(k)
+        });
+        Keymap { bindings: [Binding::of(up), Binding::of(down), Binding::of(left), Binding::of(right)] }
+    }
+}
+
+/// US character and its shifted form, then what the same key gives on the
+/// layout. Only keys that differ and produce ASCII are listed; the AZERTY
+/// number row is left alone so that the number keys still pick a mode, and
+/// so is the `!`/`§` key.
+const AZERTY: [(char, char, char, char); 8] = [
+    ('q', 'Q', 'a', 'A'), ('w', 'W', 'z', 'Z'), ('a', 'A', 'q', 'Q'), ('z', 'Z', 'w', 'W'),
+    (';', ':', 'm', 'M'), ('m', 'M', ',', '?'), (',', '<', ';', '.'), ('.', '>', ':', '/')
+];
+
+const DVORAK: [(char, char, char, char); 33] = [
+    ('q', 'Q', '\'', '"'), ('w', 'W', ',', '<'), ('e', 'E', '.', '>'), ('r', 'R', 'p', 'P'),
+    ('t', 'T', 'y', 'Y'), ('y', 'Y', 'f', 'F'), ('u', 'U', 'g', 'G'), ('i', 'I', 'c', 'C'),
+    ('o', 'O', 'r', 'R'), ('p', 'P', 'l', 'L'), ('[', '{', '/', '?'), (']', '}', '=', '+'),
+    ('s', 'S', 'o', 'O'), ('d', 'D', 'e', 'E'), ('f', 'F', 'u', 'U'), ('g', 'G', 'i', 'I'),
+    ('h', 'H', 'd', 'D'), ('j', 'J', 'h', 'H'), ('k', 'K', 't', 'T'), ('l', 'L', 'n', 'N'),
+    (';', ':', 's', 'S'), ('\'', '"', '-', '_'), ('z', 'Z', ';', ':'), ('x', 'X', 'q', 'Q'),
+    ('c', 'C', 'j', 'J'), ('v', 'V', 'k', 'K'), ('b', 'B', 'x', 'X'), ('n', 'N', 'b', 'B'),
+    (',', '<', 'w', 'W'), ('.', '>', 'v', 'V'), ('/', '?', 'z', 'Z'), ('-', '_', '[', '{'),
+    ('=', '+', ']', '}')
+];
+
+#[cfg(test)]
+mod tests {
+    use pc_keyboard::{DecodedKey, KeyCode};
+
+    use super::{KeyboardLayout, LAYOUTS};
+    use crate::keymap::{Binding, PLAYER1_KEYS};
+
+    #[test]
+    fn translates_characters_typed_on_a_us_decoder() {
+        let cases = [
+            (KeyboardLayout::Qwerty, 'q', 'q'), (KeyboardLayout::Qwerty, ';', ';'),
+            (KeyboardLayout::Azerty, 'q', 'a'), (KeyboardLayout::Azerty, 'Q', 'A'),
+            (KeyboardLayout::Azerty, ';', 'm'), (KeyboardLayout::Azerty, 'M', '?'),
+            (KeyboardLayout::Azerty, '/', '/'), (KeyboardLayout::Azerty, '1', '1'),
+            (KeyboardLayout::Dvorak, 's', 'o'), (KeyboardLayout::Dvorak, 'D', 'E'),
+            (KeyboardLayout::Dvorak, '\'', '-'), (KeyboardLayout::Dvorak, '=', ']'),
+            (KeyboardLayout::Dvorak, '1', '1')
+        ];
+        for (layout, typed, expected) in cases {
+            assert_eq!(layout.translate(DecodedKey::Unicode(typed)), DecodedKey::Unicode(expected), "{:?} {:?}", layout, typed);
+        }
+    }
+
+    #[test]
+    fn raw_keys_pass_through() {
+        for layout in LAYOUTS {
+            assert_eq!(layout.translate(DecodedKey::RawKey(KeyCode::ArrowUp)), DecodedKey::RawKey(KeyCode::ArrowUp));
+        }
+    }
+
+    #[test]
+    fn player_one_keys_stay_in_the_wasd_position() {
+        let cases = [
+            (KeyboardLayout::Qwerty, ['w', 's', 'a', 'd']),
+            (KeyboardLayout::Azerty, ['z', 's', 'q', 'd']),
+            (KeyboardLayout::Dvorak, [',', 'o', 'a', 'e'])
+        ];
+        for (layout, expected) in cases {
+            assert_eq!(layout.player1_keys().bindings, expected.map(Binding::Char), "{:?}", layout);
+        }
+        assert_eq!(KeyboardLayout::Qwerty.player1_keys(), PLAYER1_KEYS);
+    }
+}
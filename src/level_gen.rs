@@ -0,0 +1,458 @@
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+use crate::{Cell, Dimensions, Dir, Position, RingBuffer};
+
+pub const MAX_DIFFICULTY: usize = 3;
+pub const UNREACHABLE: u16 = u16::MAX;
+const RUNWAY: i16 = 6;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Layout {
+    Obstacles,
+    Rooms,
+    Maze
+}
+
+impl Layout {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layout::Obstacles => "Obstacles",
+            Layout::Rooms => "Rooms",
+            Layout::Maze => "Maze"
+        }
+    }
+}
+
+/// How two-player boards are mirrored about their centre so that neither
+/// player gets a better half.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Symmetry {
+    Off,
+    Point,
+    Mirror
+}
+
+impl Symmetry {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Symmetry::Off => "Off",
+            Symmetry::Point => "Point",
+            Symmetry::Mirror => "Mirror"
+        }
+    }
+
+    pub fn image<const WIDTH: usize, const HEIGHT: usize>(&self, p: Position<WIDTH,HEIGHT>, dims: Dimensions) -> Position<WIDTH,HEIGHT> {
+        let (height, width) = (dims.height as i16, dims.width as i16);
+        match self {
+            Symmetry::Off => p,
+            Symmetry::Point => Position {row: height - 1 - p.row, col: width - 1 - p.col},
+            Symmetry::Mirror => Position {row: p.row, col: width - 1 - p.col}
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct LevelSpec {
+    pub layout: Layout,
+    pub seed: u64,
+    pub difficulty: usize,
+    pub players: usize,
+    pub symmetry: Symmetry,
+    pub dims: Dimensions
+}
+
+pub struct GeneratedLevel<const WIDTH: usize, const HEIGHT: usize> {
+    pub cells: [[Cell; WIDTH]; HEIGHT],
+    pub spawns: [(Position<WIDTH,HEIGHT>, Dir); 2]
+}
+
+/// Builds a walled board of `spec.dims`; cells beyond those dimensions are
+/// walls. Every empty cell is reachable from each
+/// spawn, and each spawn has at least `RUNWAY` clear cells ahead of it.
+/// Two-player boards are symmetric under `spec.symmetry`, spawns included.
+pub fn generate<const WIDTH: usize, const HEIGHT: usize>(spec: &LevelSpec) -> GeneratedLevel<WIDTH,HEIGHT> {
+    let mut rng = SmallRng::seed_from_u64(spec.seed);
+    let difficulty = spec.difficulty.clamp(1, MAX_DIFFICULTY);
+    let symmetry = if spec.players > 1 { spec.symmetry } else { Symmetry::Off };
+    let dims = Dimensions {width: spec.dims.width.min(WIDTH), height: spec.dims.height.min(HEIGHT)};
+    let mut cells = [[Cell::Empty; WIDTH]; HEIGHT];
+    match spec.layout {
+        Layout::Obstacles => obstacles(&mut cells, dims, &mut rng, difficulty),
+        Layout::Rooms => rooms(&mut cells, dims, &mut rng, difficulty),
+        Layout::Maze => maze(&mut cells, dims, &mut rng, difficulty)
+    }
+    reflect(&mut cells, dims, symmetry);
+    border(&mut cells, dims);
+    let spawns = spawns::<WIDTH,HEIGHT>(symmetry, dims);
+    let centre = Position {row: (dims.height / 2) as i16, col: (dims.width / 2) as i16};
+    for (pos, dir) in spawns.iter().take(spec.players.max(1)) {
+        let mut runway_end = *pos;
+        for _ in 0..RUNWAY {
+            set(&mut cells, dims, runway_end, Cell::Empty);
+            runway_end = runway_end.neighbor(*dir);
+        }
+        carve_path(&mut cells, dims, *pos, centre, 1);
+    }
+    symmetrize(&mut cells, dims, symmetry, Cell::Empty);
+    seal_unreachable(&mut cells, spawns[0].0);
+    GeneratedLevel {cells, spawns}
+}
+
+/// Player one starts in the upper left heading east; player two starts at its
+/// image, reflected through the centre unless mirroring left to right.
+pub fn spawns<const WIDTH: usize, const HEIGHT: usize>(symmetry: Symmetry, dims: Dimensions) -> [(Position<WIDTH,HEIGHT>, Dir); 2] {
+    let row = (dims.height / 4).max(1) as i16;
+    let col = 2.min(dims.width.saturating_sub(2)) as i16;
+    let first = Position {row, col};
+    let second = match symmetry {
+        Symmetry::Mirror => Symmetry::Mirror.image(first, dims),
+        Symmetry::Off | Symmetry::Point => Symmetry::Point.image(first, dims)
+    };
+    [(first, Dir::E), (second, Dir::W)]
+}
+
+/// Copies the half of the board holding player one's spawn onto the other half.
+fn reflect<const WIDTH: usize, const HEIGHT: usize>(cells: &mut [[Cell; WIDTH]; HEIGHT], dims: Dimensions, symmetry: Symmetry) {
+    for row in 0..dims.height {
+        for col in 0..dims.width {
+            let (image_row, image_col) = symmetry.image(Position::<WIDTH,HEIGHT> {row: row as i16, col: col as i16}, dims).row_col();
+            if (row, col) > (image_row, image_col) {
+                cells[row][col] = cells[image_row][image_col];
+            }
+        }
+    }
+}
+
+/// Makes the board symmetric, resolving disagreements between a cell and its
+/// image in favour of `keep`.
+fn symmetrize<const WIDTH: usize, const HEIGHT: usize>(cells: &mut [[Cell; WIDTH]; HEIGHT], dims: Dimensions, symmetry: Symmetry, keep: Cell) {
+    if symmetry == Symmetry::Off {
+        return;
+    }
+    for row in 0..dims.height {
+        for col in 0..dims.width {
+            let (image_row, image_col) = symmetry.image(Position::<WIDTH,HEIGHT> {row: row as i16, col: col as i16}, dims).row_col();
+            if cells[image_row][image_col] == keep {
+                cells[row][col] = keep;
+            }
+        }
+    }
+}
+
+fn is_interior<const WIDTH: usize, const HEIGHT: usize>(p: Position<WIDTH,HEIGHT>, dims: Dimensions) -> bool {
+    0 < p.row && (p.row as usize) < dims.height - 1 && 0 < p.col && (p.col as usize) < dims.width - 1
+}
+
+fn set<const WIDTH: usize, const HEIGHT: usize>(cells: &mut [[Cell; WIDTH]; HEIGHT], dims: Dimensions, p: Position<WIDTH,HEIGHT>, cell: Cell) {
+    if is_interior(p, dims) {
+        let (row, col) = p.row_col();
+        cells[row][col] = cell;
+    }
+}
+
+fn fill_rect<const WIDTH: usize, const HEIGHT: usize>(cells: &mut [[Cell; WIDTH]; HEIGHT], dims: Dimensions, row: usize, col: usize, height: usize, width: usize, cell: Cell) {
+    for r in row..row + height {
+        for c in col..col + width {
+            set(cells, dims, Position {row: r as i16, col: c as i16}, cell);
+        }
+    }
+}
+
+/// Walls the edge of `dims` and everything beyond it.
+fn border<const WIDTH: usize, const HEIGHT: usize>(cells: &mut [[Cell; WIDTH]; HEIGHT], dims: Dimensions) {
+    for (row, row_cells) in cells.iter_mut().enumerate() {
+        for (col, cell) in row_cells.iter_mut().enumerate() {
+            if row == 0 || row >= dims.height - 1 || col == 0 || col >= dims.width - 1 {
+                *cell = Cell::Wall;
+            }
+        }
+    }
+}
+
+/// Carves an L-shaped corridor: along `from`'s row, then along `to`'s column.
+pub(crate) fn carve_path<const WIDTH: usize, const HEIGHT: usize>(cells: &mut [[Cell; WIDTH]; HEIGHT], dims: Dimensions, from: Position<WIDTH,HEIGHT>, to: Position<WIDTH,HEIGHT>, width: usize) {
+    let (c0, c1) = (from.col.min(to.col), from.col.max(to.col));
+    let (r0, r1) = (from.row.min(to.row), from.row.max(to.row));
+    fill_rect(cells, dims, from.row as usize, c0 as usize, width, (c1 - c0) as usize + width, Cell::Empty);
+    fill_rect(cells, dims, r0 as usize, to.col as usize, (r1 - r0) as usize + width, width, Cell::Empty);
+}
+
+fn obstacles<const WIDTH: usize, const HEIGHT: usize>(cells: &mut [[Cell; WIDTH]; HEIGHT], dims: Dimensions, rng: &mut SmallRng, difficulty: usize) {
+    let count = dims.width * dims.height * difficulty / 120;
+    for _ in 0..count {
+        let (height, width) = if rng.gen_bool(0.5) {
+            (1, rng.gen_range(1..=2 + 2 * difficulty))
+        } else {
+            (rng.gen_range(1..=1 + difficulty), rng.gen_range(1..=2))
+        };
+        let row = rng.gen_range(1..dims.height.max(2));
+        let col = rng.gen_range(1..dims.width.max(2));
+        fill_rect(cells, dims, row, col, height, width, Cell::Wall);
+    }
+}
+
+fn rooms<const WIDTH: usize, const HEIGHT: usize>(cells: &mut [[Cell; WIDTH]; HEIGHT], dims: Dimensions, rng: &mut SmallRng, difficulty: usize) {
+    fill_rect(cells, dims, 0, 0, dims.height, dims.width, Cell::Wall);
+    let count = 4 + 2 * difficulty;
+    let corridor = if difficulty < MAX_DIFFICULTY { 2 } else { 1 };
+    let max_height = (dims.height / 3).max(3);
+    let max_width = (dims.width / 4).max(4);
+    let mut previous: Option<Position<WIDTH,HEIGHT>> = None;
+    for _ in 0..count {
+        let height = rng.gen_range(3..=max_height.saturating_sub(difficulty).max(3));
+        let width = rng.gen_range(4..=max_width.saturating_sub(2 * difficulty).max(4));
+        let row = rng.gen_range(1..dims.height.saturating_sub(height).max(2));
+        let col = rng.gen_range(1..dims.width.saturating_sub(width).max(2));
+        fill_rect(cells, dims, row, col, height, width, Cell::Empty);
+        let centre = Position {row: (row + height / 2) as i16, col: (col + width / 2) as i16};
+        if let Some(previous) = previous {
+            carve_path(cells, dims, previous, centre, corridor);
+        }
+        previous = Some(centre);
+    }
+}
+
+/// Hunt-and-kill maze over a coarse grid whose passages are
+/// `MAX_DIFFICULTY + 1 - difficulty` cells wide, with a few extra openings so
+/// that snakes are not trapped in dead ends.
+fn maze<const WIDTH: usize, const HEIGHT: usize>(cells: &mut [[Cell; WIDTH]; HEIGHT], dims: Dimensions, rng: &mut SmallRng, difficulty: usize) {
+    fill_rect(cells, dims, 0, 0, dims.height, dims.width, Cell::Wall);
+    let passage = MAX_DIFFICULTY + 1 - difficulty;
+    let period = passage + 1;
+    let rows = dims.height.saturating_sub(2) / period;
+    let cols = dims.width.saturating_sub(2) / period;
+    if rows == 0 || cols == 0 {
+        return;
+    }
+    let mut visited = [[false; WIDTH]; HEIGHT];
+    let carve_cell = |cells: &mut [[Cell; WIDTH]; HEIGHT], r: usize, c: usize| {
+        fill_rect(cells, dims, 1 + r * period, 1 + c * period, passage, passage, Cell::Empty);
+    };
+    let carve_link = |cells: &mut [[Cell; WIDTH]; HEIGHT], r: usize, c: usize, dir: Dir| {
+        let (top, left) = (1 + r * period, 1 + c * period);
+        match dir {
+            Dir::N => fill_rect(cells, dims, top - 1, left, 1, passage, Cell::Empty),
+            Dir::S => fill_rect(cells, dims, top + passage, left, 1, passage, Cell::Empty),
+            Dir::W => fill_rect(cells, dims, top, left - 1, passage, 1, Cell::Empty),
+            Dir::E => fill_rect(cells, dims, top, left + passage, passage, 1, Cell::Empty)
+        }
+    };
+    let step = |r: usize, c: usize, dir: Dir| -> Option<(usize, usize)> {
+        match dir {
+            Dir::N if r > 0 => Some((r - 1, c)),
+            Dir::S if r + 1 < rows => Some((r + 1, c)),
+            Dir::W if c > 0 => Some((r, c - 1)),
+            Dir::E if c + 1 < cols => Some((r, c + 1)),
+            _ => None
+        }
+    };
+    let dirs = [Dir::N, Dir::S, Dir::E, Dir::W];
+    let (mut r, mut c) = (rng.gen_range(0..rows), rng.gen_range(0..cols));
+    visited[r][c] = true;
+    carve_cell(cells, r, c);
+    loop {
+        let mut options = [Dir::N; 4];
+        let mut n = 0;
+        for dir in dirs {
+            if let Some((nr, nc)) = step(r, c, dir) {
+                if !visited[nr][nc] {
+                    options[n] = dir;
+                    n += 1;
+                }
+            }
+        }
+        if n > 0 {
+            let dir = options[rng.gen_range(0..n)];
+            carve_link(cells, r, c, dir);
+            let (nr, nc) = step(r, c, dir).unwrap();
+            r = nr;
+            c = nc;
+            visited[r][c] = true;
+            carve_cell(cells, r, c);
+            continue;
+        }
+        let mut hunted = None;
+        'hunt: for hr in 0..rows {
+            for hc in 0..cols {
+                if !visited[hr][hc] {
+                    for dir in dirs {
+                        if let Some((nr, nc)) = step(hr, hc, dir) {
+                            if visited[nr][nc] {
+                                hunted = Some((hr, hc, dir));
+                                break 'hunt;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        match hunted {
+            Some((hr, hc, dir)) => {
+                carve_link(cells, hr, hc, dir);
+                r = hr;
+                c = hc;
+                visited[r][c] = true;
+                carve_cell(cells, r, c);
+            }
+            None => break
+        }
+    }
+    let loops = rows * cols / (2 * difficulty + 2);
+    for _ in 0..loops {
+        let (r, c) = (rng.gen_range(0..rows), rng.gen_range(0..cols));
+        let dir = dirs[rng.gen_range(0..dirs.len())];
+        if step(r, c, dir).is_some() {
+            carve_link(cells, r, c, dir);
+        }
+    }
+}
+
+/// Walls off every empty cell that cannot be reached from `start`.
+pub(crate) fn seal_unreachable<const WIDTH: usize, const HEIGHT: usize>(cells: &mut [[Cell; WIDTH]; HEIGHT], start: Position<WIDTH,HEIGHT>) {
+    let reached = reachable(cells, start);
+    for (row, row_cells) in cells.iter_mut().enumerate() {
+        for (col, cell) in row_cells.iter_mut().enumerate() {
+            if *cell != Cell::Wall && !reached[row][col] {
+                *cell = Cell::Wall;
+            }
+        }
+    }
+}
+
+fn reachable<const WIDTH: usize, const HEIGHT: usize>(cells: &[[Cell; WIDTH]; HEIGHT], start: Position<WIDTH,HEIGHT>) -> [[bool; WIDTH]; HEIGHT] {
+    let steps = distances(cells, start, |cell| cell != Cell::Wall);
+    let mut reached = [[false; WIDTH]; HEIGHT];
+    for (row, row_steps) in steps.iter().enumerate() {
+        for (col, step) in row_steps.iter().enumerate() {
+            reached[row][col] = *step != UNREACHABLE;
+        }
+    }
+    reached
+}
+
+/// Breadth-first path lengths from `start` through cells accepted by
+/// `passable`, with `UNREACHABLE` for cells that cannot be reached.
+pub fn distances<const WIDTH: usize, const HEIGHT: usize>(cells: &[[Cell; WIDTH]; HEIGHT], start: Position<WIDTH,HEIGHT>, passable: impl Fn(Cell) -> bool) -> [[u16; WIDTH]; HEIGHT] {
+    let mut steps = [[UNREACHABLE; WIDTH]; HEIGHT];
+    fill_distances(cells, start, passable, &mut steps);
+    steps
+}
+
+/// Like `distances`, but writes into a buffer the caller can reuse.
+pub fn fill_distances<const WIDTH: usize, const HEIGHT: usize>(cells: &[[Cell; WIDTH]; HEIGHT], start: Position<WIDTH,HEIGHT>, passable: impl Fn(Cell) -> bool, steps: &mut [[u16; WIDTH]; HEIGHT]) {
+    for row in steps.iter_mut() {
+        row.fill(UNREACHABLE);
+    }
+    search(cells, start, passable, |p, step| {
+        let (row, col) = p.row_col();
+        let first = steps[row][col] == UNREACHABLE;
+        if first {
+            steps[row][col] = step;
+        }
+        first
+    });
+}
+
+/// Visits the cells reachable from `start` through cells accepted by
+/// `passable` in breadth-first order. `enter` gets each cell with its path
+/// length and returns false if the cell was visited before.
+pub fn search<const WIDTH: usize, const HEIGHT: usize>(cells: &[[Cell; WIDTH]; HEIGHT], start: Position<WIDTH,HEIGHT>, passable: impl Fn(Cell) -> bool, mut enter: impl FnMut(Position<WIDTH,HEIGHT>, u16) -> bool) {
+    if !start.is_legal() || !enter(start, 0) {
+        return;
+    }
+    let mut queue: RingBuffer<Position<WIDTH,HEIGHT>, WIDTH, HEIGHT> = RingBuffer::new(start);
+    queue.push_head(start);
+    let (mut step, mut this_step, mut next_step) = (0, 1, 0);
+    while let Some(p) = queue.pop_tail() {
+        for dir in [Dir::N, Dir::S, Dir::E, Dir::W] {
+            let next = p.neighbor(dir);
+            if next.is_legal() {
+                let (row, col) = next.row_col();
+                if passable(cells[row][col]) && enter(next, step + 1) {
+                    queue.push_head(next);
+                    next_step += 1;
+                }
+            }
+        }
+        this_step -= 1;
+        if this_step == 0 {
+            (step, this_step, next_step) = (step + 1, next_step, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{distances, generate, Layout, LevelSpec, Symmetry, MAX_DIFFICULTY, RUNWAY, UNREACHABLE};
+    use crate::{Cell, Dimensions, Position};
+
+    const WIDTH: usize = 80;
+    const HEIGHT: usize = 23;
+    const LAYOUTS: [Layout; 3] = [Layout::Obstacles, Layout::Rooms, Layout::Maze];
+    const SYMMETRIES: [Symmetry; 3] = [Symmetry::Off, Symmetry::Point, Symmetry::Mirror];
+
+    fn specs() -> impl Iterator<Item = LevelSpec> {
+        LAYOUTS.into_iter().flat_map(|layout| SYMMETRIES.into_iter().flat_map(move |symmetry| {
+            (1..=MAX_DIFFICULTY).flat_map(move |difficulty| (0..4).flat_map(move |seed| (1..=2).map(move |players| LevelSpec {
+                layout, seed, difficulty, players, symmetry, dims: Dimensions {width: WIDTH, height: HEIGHT}
+            })))
+        }))
+    }
+
+    #[test]
+    fn every_empty_cell_is_reachable_from_every_spawn() {
+        for spec in specs() {
+            let level = generate::<WIDTH,HEIGHT>(&spec);
+            for (pos, _) in level.spawns.iter().take(spec.players) {
+                let steps = distances(&level.cells, *pos, |cell| cell != Cell::Wall);
+                for (row, cells) in level.cells.iter().enumerate() {
+                    for (col, cell) in cells.iter().enumerate() {
+                        if *cell == Cell::Empty {
+                            assert_ne!(steps[row][col], UNREACHABLE, "{:?}: ({}, {}) from {:?}", spec, row, col, pos);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_spawn_has_a_clear_runway() {
+        for spec in specs() {
+            let level = generate::<WIDTH,HEIGHT>(&spec);
+            for (pos, dir) in level.spawns.iter().take(spec.players) {
+                let mut p = *pos;
+                for _ in 0..RUNWAY {
+                    let (row, col) = p.row_col();
+                    assert_eq!(level.cells[row][col], Cell::Empty, "{:?}: runway from {:?}", spec, pos);
+                    p = p.neighbor(*dir);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn two_player_boards_match_their_image() {
+        let dims = Dimensions {width: WIDTH, height: HEIGHT};
+        for spec in specs().filter(|spec| spec.players == 2) {
+            let level = generate::<WIDTH,HEIGHT>(&spec);
+            for p in (0..HEIGHT).flat_map(|row| (0..WIDTH).map(move |col| Position::<WIDTH,HEIGHT> {row: row as i16, col: col as i16})) {
+                let (row, col) = p.row_col();
+                let (image_row, image_col) = spec.symmetry.image(p, dims).row_col();
+                assert_eq!(level.cells[row][col], level.cells[image_row][image_col], "{:?}: ({}, {})", spec, row, col);
+            }
+        }
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_level() {
+        for spec in specs() {
+            let (first, second) = (generate::<WIDTH,HEIGHT>(&spec), generate::<WIDTH,HEIGHT>(&spec));
+            assert!(first.cells == second.cells && first.spawns == second.spawns, "{:?}", spec);
+        }
+        let spec = specs().next().unwrap();
+        let other = LevelSpec {seed: spec.seed + 1, ..spec};
+        assert!(generate::<WIDTH,HEIGHT>(&spec).cells != generate::<WIDTH,HEIGHT>(&other).cells);
+    }
+}
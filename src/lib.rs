@@ -1,36 +1,125 @@
 #![no_std]
 
+mod animation;
+mod frame;
+mod graphics;
+mod keymap;
+mod layout;
+mod level_gen;
+mod menu;
+mod ring_buffer;
+mod serial;
+mod sound;
+mod text_mode;
+mod theme;
+
 use pc_keyboard::{DecodedKey, KeyCode};
-use pluggable_interrupt_os::vga_buffer::{plot, Color, ColorCode, BUFFER_HEIGHT, BUFFER_WIDTH, plot_str, plot_num, clear_row};
+use pluggable_interrupt_os::vga_buffer::{Color, ColorCode, BUFFER_HEIGHT, BUFFER_WIDTH};
     use core::option::Option::Some;
 use core::{
     clone::Clone,
     cmp::{Eq, PartialEq},
+    fmt::{self, Write},
     iter::Iterator,
     marker::Copy,
     prelude::rust_2024::derive,
 };
 use rand::SeedableRng;
 use rand::rngs::SmallRng;
-use rand::RngCore;
+use rand::Rng;
+pub use animation::{Animation, Animations, Effect, FLASH_TICKS, MAX_ANIMATIONS};
+pub use frame::{Frame, Screen, ScreenCell};
+pub use graphics::{set_mode_13h, PixelScreen, Renderer, Tile};
+use graphics::font_glyph;
+pub use keymap::{rebind, Binding, Keymap, DIRS, DIR_NAMES, PLAYER1_KEYS, PLAYER2_KEYS};
+pub use layout::{KeyboardLayout, LAYOUTS};
+pub use level_gen::{distances, fill_distances, generate, search, GeneratedLevel, Layout, LevelSpec, Symmetry, MAX_DIFFICULTY, UNREACHABLE};
+pub use menu::{Edges, MenuItem, Mode, Settings, EDGES, HIGH_SCORES, LEVELS, MAX_COUNTDOWN, MAX_SPEED, MENU, MODES, SYMMETRIES, WORLDS};
+pub use ring_buffer::{RingBuffer, RingBufferIter};
+pub use serial::Serial;
+pub use sound::Sound;
+pub use text_mode::{set_text_mode, write_cell, Font, TextMode, TextScreen, MAX_ROWS};
+pub use theme::{color_name, cycle, Body, Head, PlayerStyle, Theme, BODIES, CLASSIC, COLOR_BLIND, DARK, HEADS, HIGH_CONTRAST, PALETTE, THEMES};
 
 
 const UPDATE_FREQUENCY: usize = 1;
 const GAME_HEIGHT: usize = BUFFER_HEIGHT - 2;
 const HEADER_SPACE: usize = BUFFER_HEIGHT - GAME_HEIGHT;
-const ARRAY_SIZE: usize = GAME_HEIGHT * BUFFER_WIDTH;
+const WORLD_WIDTH: usize = 2 * BUFFER_WIDTH;
+const WORLD_HEIGHT: usize = 2 * (MAX_ROWS - HEADER_SPACE);
+const SLOWEST_UPDATE_FREQUENCY: usize = 4;
+const FOOD_POINTS: usize = 10;
+const STREAK_TICKS: usize = 36;
+const STREAK_POINTS: usize = 5;
+const SURVIVAL_TICKS: usize = 18;
+const TICKS_PER_SECOND: usize = 18;
+const STARTING_LIVES: usize = 3;
+const RESPAWN_STEPS: usize = 10;
+const SUMMARY_LABEL_WIDTH: usize = 16;
+const SUMMARY_COLUMN_WIDTH: usize = 10;
+const SUMMARY_HEIGHT: usize = 12;
+const SETUP_HEIGHT: usize = 10;
+const SETUP_ROWS: usize = 3;
+const MAX_DIRTY: usize = 16;
+const MINIMAP_WIDTH: usize = 20;
+const MINIMAP_HEIGHT: usize = 10;
+const FOG_RADIUS: i32 = 10;
+const GO_TICKS: usize = TICKS_PER_SECOND / 2;
+const BIG_PIXEL_WIDTH: usize = 4;
+const BIG_PIXEL_HEIGHT: usize = 2;
+const TAIL_GLYPH: char = '\u{F9}';
+const MENU_PROMPT: &str = "Up/Down: choose  Left/Right: change  Enter: select  1-3: quick start";
+const KEYBOARD_PROMPT: &str = "Keys are read as US keys, then relabelled to match the layout";
+const OVER_PROMPT: &str = "Enter: menu  1: Solo  2: Duel  3: Solo+Lives";
+const BACK_PROMPT: &str = "Enter or Esc: back to the menu";
+const CONTROLS_PROMPT: &str = "Move to a key and press Enter to change it  Esc: back to the menu";
+const REBIND_PROMPT: &str = "Press the new key  Esc: cancel";
+const CONTROLS_ROWS: usize = 5;
+const EXIT_MESSAGE: &str = "Snake has exited. Press any key to play again.";
+const SETUP_PROMPT: &str = "Each player's keys: Up/Down pick, Left/Right change  Enter: done";
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
 pub struct SnakeGame<const WIDTH: usize, const HEIGHT: usize> {
     cells: [[Cell; WIDTH]; HEIGHT],
+    glyphs: [[u8; WIDTH]; HEIGHT],
+    dims: Dimensions,
+    cameras: [Position<WIDTH,HEIGHT>; 2],
+    minimap: bool,
+    seen: [[bool; WIDTH]; HEIGHT],
+    animations: Animations<WIDTH,HEIGHT>,
+    text_mode: TextMode,
+    font: Option<Font>,
+    renderer: Renderer,
     snake: Snake<WIDTH,HEIGHT>,
     snake2: Snake<WIDTH,HEIGHT>,
     status: Status,
     last_key: Option<Dir>,
     last_key2: Option<Dir>,
     countdown: usize,
+    starting: usize,
+    update_frequency: usize,
     total_ticks: usize,
-    two_player: bool
+    round_start: usize,
+    two_player: bool,
+    lives: usize,
+    starting_lives: usize,
+    invulnerable: usize,
+    spawn: Position<WIDTH,HEIGHT>,
+    spawn_icon: char,
+    settings: Settings,
+    menu_row: usize,
+    controls_cursor: (usize, usize),
+    rebinding: bool,
+    high_scores: [[usize; HIGH_SCORES]; 3],
+    beep: usize,
+    setup_row: [usize; 2],
+    dirty: [Position<WIDTH,HEIGHT>; MAX_DIRTY],
+    dirty_len: usize,
+    redraw_all: bool,
+    redraw_header: bool,
+    drawn_status: Status,
+    frame: ScreenFrame,
+    shown: ScreenFrame
 }
 
 #[derive(Debug,Copy,Clone,Eq,PartialEq)]
@@ -40,6 +129,18 @@ pub enum Dir {
 }
 
 impl Dir {
+    fn body_glyph(&self, other: Dir) -> char {
+        match (self, other) {
+            (Dir::N, Dir::S) | (Dir::S, Dir::N) => '\u{B3}',
+            (Dir::E, Dir::W) | (Dir::W, Dir::E) => '\u{C4}',
+            (Dir::S, Dir::E) | (Dir::E, Dir::S) => '\u{DA}',
+            (Dir::S, Dir::W) | (Dir::W, Dir::S) => '\u{BF}',
+            (Dir::N, Dir::E) | (Dir::E, Dir::N) => '\u{C0}',
+            (Dir::N, Dir::W) | (Dir::W, Dir::N) => '\u{D9}',
+            _ => 'o'
+        }
+    }
+
     fn icon(&self) -> char {
         match self {
             Dir::N => '^',
@@ -104,20 +205,158 @@ impl <const WIDTH: usize, const HEIGHT: usize> Position<WIDTH,HEIGHT> {
     }
 }
 
+/// The part of a board in play, which may be smaller than its capacity.
+#[derive(Debug,Copy,Clone,Eq,PartialEq)]
+pub struct Dimensions {
+    pub width: usize, pub height: usize
+}
+
+impl Dimensions {
+    pub fn contains<const WIDTH: usize, const HEIGHT: usize>(&self, p: Position<WIDTH,HEIGHT>) -> bool {
+        p.is_legal() && (p.row as usize) < self.height && (p.col as usize) < self.width
+    }
+}
+
+#[derive(Copy,Clone,Eq,PartialEq,Debug,Default)]
+pub struct Score {
+    food: usize, streak: usize, survival: usize,
+    streak_len: usize, last_food_tick: usize
+}
+
+impl Score {
+    fn new(start_tick: usize) -> Self {
+        Score {last_food_tick: start_tick, ..Score::default()}
+    }
+
+    pub fn total(&self) -> usize {
+        self.food + self.streak + self.survival
+    }
+
+    pub fn food(&self) -> usize {
+        self.food
+    }
+
+    pub fn streak(&self) -> usize {
+        self.streak
+    }
+
+    pub fn survival(&self) -> usize {
+        self.survival
+    }
+
+    fn eat(&mut self, tick: usize, speed: usize) {
+        self.food += FOOD_POINTS * speed;
+        if tick.wrapping_sub(self.last_food_tick) <= STREAK_TICKS {
+            self.streak_len += 1;
+            self.streak += STREAK_POINTS * self.streak_len;
+        } else {
+            self.streak_len = 0;
+        }
+        self.last_food_tick = tick;
+    }
+
+    fn survive(&mut self, ticks: usize) {
+        self.survival = ticks / SURVIVAL_TICKS;
+    }
+}
+
+#[derive(Copy,Clone,Eq,PartialEq,Debug)]
+pub enum Cause {
+    Wall,
+    OwnBody,
+    Opponent,
+    HeadOn,
+    Boundary
+}
+
+impl Cause {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Cause::Wall => "Wall",
+            Cause::OwnBody => "Own body",
+            Cause::Opponent => "Opponent",
+            Cause::HeadOn => "Head-on",
+            Cause::Boundary => "Boundary"
+        }
+    }
+}
+
 #[derive(Copy,Clone,Eq,PartialEq,Debug)]
+pub struct Death<const WIDTH: usize, const HEIGHT: usize> {
+    cause: Cause, pos: Position<WIDTH,HEIGHT>
+}
+
+impl <const WIDTH: usize, const HEIGHT: usize> Death<WIDTH,HEIGHT> {
+    pub fn cause(&self) -> Cause {
+        self.cause
+    }
+
+    /// The cell the snake tried to enter, or for `Cause::Boundary` the edge cell its head was on.
+    pub fn pos(&self) -> Position<WIDTH,HEIGHT> {
+        self.pos
+    }
+}
+
+impl <const WIDTH: usize, const HEIGHT: usize> fmt::Display for Death<WIDTH,HEIGHT> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at row {}, column {}", self.cause.description(), self.pos.row, self.pos.col)
+    }
+}
+
+#[derive(Copy,Clone,Eq,PartialEq,Debug,Default)]
+struct Stats<const WIDTH: usize, const HEIGHT: usize> {
+    food_eaten: usize, max_length: usize, ticks: usize, death: Option<Death<WIDTH,HEIGHT>>
+}
+
+/// `body` holds every cell behind the head, with the neck at its head and
+/// the tail at its tail.
+#[derive(Clone,Eq,PartialEq,Debug)]
 struct Snake<const WIDTH: usize, const HEIGHT: usize> {
     pos: Position<WIDTH,HEIGHT>, dir: Dir, size: usize, 
-    body: [Position<WIDTH,HEIGHT>; ARRAY_SIZE], insert_index: usize, 
-    remove_index: usize
+    body: RingBuffer<Position<WIDTH,HEIGHT>, WIDTH, HEIGHT>,
+    score: Score, stats: Stats<WIDTH,HEIGHT>
 }
 
 impl <const WIDTH: usize, const HEIGHT: usize> Snake<WIDTH,HEIGHT> {
     fn new(pos: Position<WIDTH,HEIGHT>, icon: char) -> Self {
-        Snake {pos, dir: Dir::from(icon), size: 0, body: [Position { col: 0, row: 0}; ARRAY_SIZE], insert_index: 0, remove_index: 0}
+        Snake {pos, dir: Dir::from(icon), size: 0, body: RingBuffer::new(pos), score: Score::default(), stats: Stats::default()}
     }
 
-    fn icon(&self) -> char {
-        self.dir.icon()
+    fn grow(&mut self, tick: usize, speed: usize) {
+        self.size += 1;
+        self.score.eat(tick, speed);
+        self.stats.food_eaten += 1;
+        self.stats.max_length = self.stats.max_length.max(self.size);
+    }
+}
+
+#[derive(Copy,Clone,Eq,PartialEq,Debug)]
+pub enum Level {
+    Classic,
+    Generated(Layout)
+}
+
+impl Level {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Classic => "Classic",
+            Level::Generated(layout) => layout.name()
+        }
+    }
+}
+
+#[derive(Copy,Clone,Eq,PartialEq,Debug)]
+pub enum World {
+    Screen,
+    Large
+}
+
+impl World {
+    pub fn name(&self) -> &'static str {
+        match self {
+            World::Screen => "Screen",
+            World::Large => "Large"
+        }
     }
 }
 
@@ -127,7 +366,14 @@ pub enum Status {
     Over,
     Over1,
     Over2,
-    Start
+    Draw,
+    Cleared,
+    Start,
+    Setup,
+    Controls,
+    HighScores,
+    Countdown,
+    Exited
 }
 
 const START1: &'static str =
@@ -181,24 +427,79 @@ const START1: &'static str =
      ################################################################################";
 
 
-pub type MainGame = SnakeGame<BUFFER_WIDTH,GAME_HEIGHT>;
+pub type MainGame = SnakeGame<WORLD_WIDTH,WORLD_HEIGHT>;
+pub type ScreenFrame = Frame<BUFFER_WIDTH,MAX_ROWS>;
+
+/// A cell `draw` never produces, so a frame filled with it differs from every drawn cell.
+fn unshown() -> ScreenCell {
+    ('\u{0}', ColorCode::new(Color::Black, Color::Black))
+}
  
 impl <const WIDTH: usize, const HEIGHT: usize> SnakeGame<WIDTH, HEIGHT> {
     pub fn new() -> Self {
-        let mut game = SnakeGame {
+        Self::with_renderer(Renderer::Text)
+    }
+
+    /// Switches the VGA card into mode 13h first when `renderer` is `Renderer::Pixels`.
+    pub fn with_renderer(renderer: Renderer) -> Self {
+        if renderer == Renderer::Pixels {
+            set_mode_13h();
+        }
+        let mut game = Self::blank(renderer);
+        if renderer == Renderer::Text {
+            let font = Font::read();
+            font.with_snake_glyphs().load();
+            game.font = Some(font);
+        }
+        game.reset();
+        game.status = Status::Start;
+        game
+    }
+
+    /// A game that has not touched the video hardware or started a round.
+    fn blank(renderer: Renderer) -> Self {
+        SnakeGame {
             cells: [[Cell::Empty; WIDTH]; HEIGHT],
+            glyphs: [[TAIL_GLYPH as u8; WIDTH]; HEIGHT],
+            dims: Self::screen_dims(TextMode::Rows25),
+            cameras: [Position { col: 0, row: 0}; 2],
+            minimap: true,
+            seen: [[false; WIDTH]; HEIGHT],
+            animations: Animations::default(),
+            text_mode: TextMode::Rows25,
+            font: None,
+            renderer,
             snake: Snake::new(Position { col: 0, row: 0}, 'v'),
             snake2: Snake::new(Position { col: 0, row: 0}, '^'),
             last_key: None,
             last_key2: None,
             status: Status::Normal,
             countdown: UPDATE_FREQUENCY,
+            starting: 0,
+            update_frequency: UPDATE_FREQUENCY,
             total_ticks: 0,
-            two_player: true
-        };
-        game.reset(true);
-        game.status = Status::Start;
-        game
+            round_start: 0,
+            two_player: true,
+            lives: 1,
+            starting_lives: 1,
+            invulnerable: 0,
+            spawn: Position { col: 0, row: 0},
+            spawn_icon: 'v',
+            settings: Settings::default(),
+            menu_row: 0,
+            controls_cursor: (0, 0),
+            rebinding: false,
+            high_scores: [[0; HIGH_SCORES]; 3],
+            beep: 0,
+            setup_row: [0; 2],
+            dirty: [Position { col: 0, row: 0}; MAX_DIRTY],
+            dirty_len: 0,
+            redraw_all: true,
+            redraw_header: true,
+            drawn_status: Status::Start,
+            frame: Frame::new((' ', ColorCode::new(Color::Black, Color::Green))),
+            shown: Frame::new(unshown())
+        }
     }
 
     pub fn tick(&mut self) {
@@ -206,292 +507,1465 @@ impl <const WIDTH: usize, const HEIGHT: usize> SnakeGame<WIDTH, HEIGHT> {
         if self.total_ticks == usize::MAX {
             self.total_ticks = 0;
         }
-        if self.countdown_complete() {
+        if self.beep > 0 {
+            self.beep -= 1;
+            if self.beep == 0 {
+                sound::stop();
+            }
+        }
+        let counting = self.status == Status::Countdown;
+        if counting {
+            self.count_down();
+        }
+        let step = self.countdown_complete();
+        if step {
             self.update();
+        }
+        if self.animate() || step || counting {
             self.draw();
         }
     }
+
+    fn play(&mut self, sound: Sound) {
+        if self.settings.sound {
+            sound::play(sound.frequency());
+            self.beep = sound.ticks();
+        }
+    }
+
+    /// Runs the countdown to the start of a round, redrawing whenever the
+    /// number on the board changes.
+    fn count_down(&mut self) {
+        let label = self.countdown_label();
+        self.starting = self.starting.saturating_sub(1);
+        if self.starting == 0 {
+            self.status = Status::Normal;
+            self.start_clock();
+        } else if self.countdown_label() != label {
+            self.play(if self.countdown_label().is_some() { Sound::Count } else { Sound::Go });
+            self.redraw_all = true;
+        }
+    }
+
+    /// The seconds left before the round starts, or `None` while showing "GO".
+    fn countdown_label(&self) -> Option<char> {
+        let seconds = self.starting.saturating_sub(GO_TICKS).div_ceil(TICKS_PER_SECOND);
+        if seconds == 0 { None } else { char::from_digit(seconds as u32, 10) }
+    }
+
+    /// Marks whatever the running animations cover for redrawing and drops
+    /// the ones that have finished. Returns whether any were running.
+    fn animate(&mut self) -> bool {
+        if self.status != Status::Normal && !self.is_over() {
+            self.animations.clear();
+        }
+        if self.animations.is_empty() {
+            return false;
+        }
+        let animations = self.animations;
+        for a in animations.iter() {
+            match a.effect {
+                Effect::Death(_) => self.redraw_all = true,
+                Effect::FadeIn => self.mark_dirty(a.pos),
+                Effect::Sparkle => {
+                    for row in a.pos.row - 1..=a.pos.row + 1 {
+                        for col in a.pos.col - 1..=a.pos.col + 1 {
+                            let p = Position {row, col};
+                            if self.dims.contains(p) {
+                                self.mark_dirty(p);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if self.is_over() {
+            self.redraw_all = true;
+        }
+        self.animations.remove_done(self.total_ticks);
+        true
+    }
     
     fn draw(&mut self) {
-        self.draw_header();
-        self.draw_board();
+        if self.status != self.drawn_status {
+            self.drawn_status = self.status;
+            self.redraw_all = true;
+        }
+        if self.status == Status::Exited {
+            if self.redraw_all {
+                self.draw_exit_screen();
+            }
+        } else if self.redraw_all {
+            self.draw_header();
+            self.draw_board();
+            self.draw_minimap();
+            if self.is_over() {
+                self.draw_summary();
+            }
+            match self.status {
+                Status::Start => self.draw_menu(),
+                Status::Setup => self.draw_setup(),
+                Status::Controls => self.draw_controls(),
+                Status::HighScores => self.draw_high_scores(),
+                _ => {}
+            }
+            if self.status == Status::Countdown {
+                match self.countdown_label() {
+                    Some(digit) => self.draw_big_text(&[digit]),
+                    None => self.draw_big_text(&['G', 'O'])
+                }
+            }
+        } else {
+            if self.redraw_header {
+                self.draw_header();
+            }
+            for i in 0..self.dirty_len {
+                self.draw_cell(self.dirty[i]);
+            }
+            if self.dirty_len > 0 && self.status == Status::Normal {
+                self.draw_minimap();
+            }
+        }
+        self.dirty_len = 0;
+        self.redraw_all = false;
+        self.redraw_header = false;
+        match self.renderer {
+            Renderer::Text => self.frame.blit(&mut self.shown, self.text_mode.rows(), &mut TextScreen),
+            Renderer::Pixels => self.frame.blit(&mut self.shown, self.text_mode.rows(), &mut PixelScreen)
+        };
+    }
+
+    fn draw_exit_screen(&mut self) {
+        let color = ColorCode::new(Color::LightGray, Color::Black);
+        self.frame = Frame::new((' ', color));
+        self.frame.plot_str(EXIT_MESSAGE, 0, 0, color);
+    }
+
+    /// Puts back the original font and 80x25 mode.
+    fn exit(&mut self) {
+        if let Some(font) = self.font {
+            set_text_mode(TextMode::Rows25, &font);
+        }
+        self.text_mode = TextMode::Rows25;
+        self.dims = self.world_dims();
+        self.shown = Frame::new(unshown());
+        self.status = Status::Exited;
+        sound::stop();
+    }
+
+    fn resume(&mut self) {
+        if let Some(font) = self.font {
+            font.with_snake_glyphs().load();
+        }
+        self.load_board(self.two_player);
+        self.status = Status::Start;
+    }
+
+    /// The largest board that fits on screen in `mode`.
+    fn screen_dims(mode: TextMode) -> Dimensions {
+        Dimensions {width: WIDTH.min(BUFFER_WIDTH), height: HEIGHT.min(mode.rows() - HEADER_SPACE)}
+    }
+
+    fn world_dims(&self) -> Dimensions {
+        match self.settings.world {
+            World::Screen => Self::screen_dims(self.text_mode),
+            World::Large => Dimensions {width: WIDTH, height: HEIGHT}
+        }
+    }
+
+    /// The screen area below the header that shows the board.
+    fn view(&self) -> Dimensions {
+        Dimensions {width: BUFFER_WIDTH, height: self.text_mode.rows() - HEADER_SPACE}
+    }
+
+    fn is_scrolling(&self) -> bool {
+        self.dims.width > self.view().width || self.dims.height > self.view().height
+    }
+
+    /// Two players on a world bigger than the screen each get half of it.
+    fn is_split(&self) -> bool {
+        self.two_player && self.is_scrolling()
+    }
+
+    fn viewport_count(&self) -> usize {
+        if self.is_split() { 2 } else { 1 }
+    }
+
+    /// The left screen column and size of the picture from camera `i`.
+    /// Split views sit either side of the divider column.
+    fn viewport(&self, i: usize) -> (usize, Dimensions) {
+        let view = self.view();
+        if !self.is_split() {
+            return (0, view);
+        }
+        let left_width = (view.width - 1) / 2;
+        match i {
+            0 => (0, Dimensions {width: left_width, height: view.height}),
+            _ => (left_width + 1, Dimensions {width: view.width - left_width - 1, height: view.height})
+        }
+    }
+
+    /// Scrolls each camera just far enough to keep its snake's head out of
+    /// the outer quarter of its viewport on every side.
+    fn follow(&mut self) {
+        for i in 0..self.viewport_count() {
+            let (_, view) = self.viewport(i);
+            let (row, col) = [&self.snake, &self.snake2][i].pos.row_col();
+            let camera = self.cameras[i];
+            let camera = Position {
+                row: scroll(camera.row as usize, row, view.height, self.dims.height) as i16,
+                col: scroll(camera.col as usize, col, view.width, self.dims.width) as i16
+            };
+            if camera != self.cameras[i] {
+                self.cameras[i] = camera;
+                self.redraw_all = true;
+            }
+        }
+    }
+
+    fn toggle_minimap(&mut self) {
+        self.minimap = !self.minimap;
+        self.redraw_all = true;
+    }
+
+    fn switch_text_mode(&mut self, mode: TextMode) {
+        if self.renderer != Renderer::Text {
+            return;
+        }
+        let font = *self.font.get_or_insert_with(Font::read);
+        set_text_mode(mode, &font.with_snake_glyphs());
+        self.text_mode = mode;
+        self.dims = self.world_dims();
+        self.shown = Frame::new(unshown());
+        self.load_board(self.two_player);
+        self.status = Status::Start;
+        self.redraw_all = true;
+    }
+
+    pub fn frame(&self) -> &ScreenFrame {
+        &self.frame
+    }
+
+    fn mark_dirty(&mut self, p: Position<WIDTH,HEIGHT>) {
+        if self.dirty[..self.dirty_len].contains(&p) {
+            return;
+        }
+        if self.dirty_len < MAX_DIRTY {
+            self.dirty[self.dirty_len] = p;
+            self.dirty_len += 1;
+        } else {
+            self.redraw_all = true;
+        }
+    }
+
+    fn header_state(&self) -> [usize; 8] {
+        let (row, col) = self.snake.pos.row_col();
+        [self.snake.score.total(), self.snake.size, self.snake2.score.total(), self.snake2.size, self.lives, self.invulnerable, row, col]
     }
     
     fn draw_header(&mut self) {
         match self.status() {
-            Status::Normal => self.draw_normal_header(),
+            Status::Normal | Status::Countdown => self.draw_normal_header(),
             Status::Over => self.draw_game_over_header(),
             Status::Over1 => self.draw_game_over_header1(),
             Status::Over2 => self.draw_game_over_header2(),
-            Status::Start => self.draw_start_header()
+            Status::Draw => self.draw_game_over_header_draw(),
+            Status::Cleared => self.draw_cleared_header(),
+            Status::Start => self.draw_start_header(),
+            Status::Setup => self.draw_setup_header(),
+            Status::Controls => {
+                self.draw_head("Controls", self.settings.theme.text);
+                self.draw_subheader(if self.rebinding { REBIND_PROMPT } else { CONTROLS_PROMPT });
+            }
+            Status::HighScores => {
+                self.draw_head("High Scores", self.settings.theme.text);
+                self.draw_subheader(BACK_PROMPT);
+            }
+            Status::Exited => {}
         }
     }
 
     fn draw_start_header(&mut self) {
-        let header_color = ColorCode::new(Color::White, Color::Green);
-        clear_row(0, Color::Green);
-        clear_row(1, Color::Green);
-        let welcome = "Welcome to snake!";
-        plot_str(welcome, 0, 0, header_color);
-        self.draw_subheader("Press 1 for One-Player Mode and 2 for Two-Player Mode.");
+        self.draw_head("Snake!", self.settings.theme.text);
+        let best = self.high_scores[self.settings.mode.index()][0];
+        plot_stat(&mut self.frame, "Best:", best, "Snake!".len() + 2, 0, self.settings.theme.header(self.settings.theme.text));
+        self.draw_subheader(if self.menu_item() == MenuItem::Keyboard { KEYBOARD_PROMPT } else { MENU_PROMPT });
     }
-    
+
+    fn draw_setup_header(&mut self) {
+        self.draw_head("Player Setup", self.settings.theme.text);
+        self.draw_subheader(SETUP_PROMPT);
+    }
+
     fn draw_normal_header(&mut self) {
-        let mut header_color = ColorCode::new(Color::Blue, Color::Green);
-        clear_row(0, Color::Green);
-        clear_row(1, Color::Green);
+        let mut header_color = self.player_color(0);
+        self.frame.clear_row(0, self.settings.theme.background);
+        self.frame.clear_row(1, self.settings.theme.background);
         if !self.two_player {
-            let score_text = "Score:";
-            plot_str(score_text, 0, 0, header_color);
-            plot_num(self.snake.size as isize, score_text.len() + 1, 0, header_color);
+            let col = plot_stat(&mut self.frame, "Score:", self.snake.score.total(), 0, 0, header_color);
+            let col = plot_stat(&mut self.frame, "Length:", self.snake.size, col + 2, 0, header_color);
+            if self.starting_lives > 1 {
+                plot_stat(&mut self.frame, "Lives:", self.lives, col + 2, 0, header_color);
+            }
+            if self.invulnerable > 0 {
+                plot_stat(&mut self.frame, "Respawning in", self.invulnerable, 0, 1, self.settings.theme.header(self.settings.theme.highlight));
+            }
         }
         else {
-            let score_text = "Player 1 Size:";
-            plot_str(score_text, 0, 0, header_color);
-            plot_num(self.snake.size as isize, score_text.len() + 1, 0, header_color);
-            let score_text = "Player 2 Size:";
-            header_color = ColorCode::new(Color::Magenta, Color::Green);
-            plot_str(score_text, WIDTH/2, 0, header_color);
-            plot_num(self.snake2.size as isize, WIDTH/2 + score_text.len() + 1, 0, header_color);
+            let col = plot_stat(&mut self.frame, "Player 1 Score:", self.snake.score.total(), 0, 0, header_color);
+            plot_stat(&mut self.frame, "Length:", self.snake.size, col + 2, 0, header_color);
+            header_color = self.player_color(1);
+            let col = plot_stat(&mut self.frame, "Player 2 Score:", self.snake2.score.total(), BUFFER_WIDTH / 2, 0, header_color);
+            plot_stat(&mut self.frame, "Length:", self.snake2.size, col + 2, 0, header_color);
+        }
+        if self.is_scrolling() {
+            self.frame.plot_str("Map(Tab)", BUFFER_WIDTH / 2, 1, self.settings.theme.header(self.settings.theme.text));
+            self.draw_position(self.player_color(0));
         }
+    }
+
+    /// Player one's head and the size of the world, right-aligned on the second header row.
+    fn draw_position(&mut self, color: ColorCode) {
+        let (row, col) = self.snake.pos.row_col();
+        let width = "Pos ".len() + num_width(row) + 1 + num_width(col) + " of ".len()
+            + num_width(self.dims.height) + 1 + num_width(self.dims.width);
+        let start = BUFFER_WIDTH.saturating_sub(width);
+        let next = self.frame.plot_str("Pos", start, 1, color);
+        let next = self.frame.plot_num(row as isize, next + 1, 1, color);
+        self.frame.plot(',', next, 1, color);
+        let next = self.frame.plot_num(col as isize, next + 1, 1, color);
+        let next = self.frame.plot_str("of", next + 1, 1, color);
+        let next = self.frame.plot_num(self.dims.height as isize, next + 1, 1, color);
+        self.frame.plot('x', next, 1, color);
+        self.frame.plot_num(self.dims.width as isize, next + 1, 1, color);
+    }
 
+    fn draw_score_breakdown(&mut self, score: Score, col: usize, row: usize, color: ColorCode) -> usize {
+        let col = plot_stat(&mut self.frame, "Score:", score.total(), col, row, color);
+        let col = plot_stat(&mut self.frame, " =", score.food(), col, row, color);
+        let col = plot_stat(&mut self.frame, " food +", score.streak(), col, row, color);
+        let col = plot_stat(&mut self.frame, " streak +", score.survival(), col, row, color);
+        self.frame.plot_str(" time", col, row, color);
+        col + " time".len()
     }
     
-    fn draw_subheader(&self, subheader: &str) {
-        plot_str(subheader, 0, 1, ColorCode::new(Color::Yellow, Color::Green));
+    fn draw_subheader(&mut self, subheader: &str) {
+        self.frame.plot_str(subheader, 0, 1, self.settings.theme.header(self.settings.theme.highlight));
     }
     
-    fn draw_head(&self, header: &str, color: Color) {
-        let header_color = ColorCode::new(color, Color::Green);
-        clear_row(0, Color::Green);
-        clear_row(1, Color::Green);
-        plot_str(header, 0, 0, header_color);
+    fn draw_head(&mut self, header: &str, color: Color) {
+        let header_color = self.settings.theme.header(color);
+        self.frame.clear_row(0, self.settings.theme.background);
+        self.frame.clear_row(1, self.settings.theme.background);
+        self.frame.plot_str(header, 0, 0, header_color);
     }
 
     fn draw_game_over_header(&mut self) {
-        self.draw_normal_header();
-        self.draw_subheader("Press 1 for One-Player Mode and 2 for Two-Player Mode.");
+        let header_color = self.player_color(0);
+        self.draw_head("Game Over:", self.settings.players[0].color);
+        let cause = self.snake.stats.death.map_or("", |d| d.cause().description());
+        self.frame.plot_str(cause, "Game Over:".len() + 1, 0, header_color);
+        let col = "Game Over:".len() + 1 + cause.len() + 2;
+        let col = self.draw_score_breakdown(self.snake.score, col, 0, header_color);
+        plot_stat(&mut self.frame, "Length:", self.snake.size, col + 2, 0, header_color);
+        self.draw_subheader(OVER_PROMPT);
     }
 
     fn draw_game_over_header1(&mut self) {
-        self.draw_head("Player 1 Wins!", Color::Blue);
-        self.draw_subheader("Press 1 for One-Player Mode and 2 for Two-Player Mode.");
+        self.draw_head("Player 1 Wins!", self.settings.players[0].color);
+        self.draw_two_player_scores();
+        self.draw_subheader(OVER_PROMPT);
     }
 
     fn draw_game_over_header2(&mut self) {
-        self.draw_head("Player 2 Wins!", Color::Magenta);
-        self.draw_subheader("Press 1 for One-Player Mode and 2 for Two-Player Mode.");
+        self.draw_head("Player 2 Wins!", self.settings.players[1].color);
+        self.draw_two_player_scores();
+        self.draw_subheader(OVER_PROMPT);
     }
-    
-    fn draw_board(&mut self) {
-        for p in self.cell_pos_iter() {
-            let (row, col) = p.row_col();
-            let (c, color) = self.get_icon_color(p, &self.cell(p));
-            plot(c, col, row + HEADER_SPACE, color);
+
+    fn draw_game_over_header_draw(&mut self) {
+        self.draw_head("It's a Draw!", self.settings.theme.text);
+        self.draw_two_player_scores();
+        self.draw_subheader(OVER_PROMPT);
+    }
+
+    fn draw_cleared_header(&mut self) {
+        let header_color = self.player_color(0);
+        self.draw_head("Board Cleared!", self.settings.theme.highlight);
+        if self.two_player {
+            self.draw_two_player_scores();
+        } else {
+            let col = self.draw_score_breakdown(self.snake.score, "Board Cleared!".len() + 2, 0, header_color);
+            plot_stat(&mut self.frame, "Length:", self.snake.size, col + 2, 0, header_color);
+        }
+        self.draw_subheader(OVER_PROMPT);
+    }
+
+    fn draw_two_player_scores(&mut self) {
+        let col = "Player 1 Wins!".len() + 2;
+        let colors = [self.player_color(0), self.player_color(1)];
+        let col = plot_stat(&mut self.frame, "P1", self.snake.score.total(), col, 0, colors[0]);
+        let mut col = plot_stat(&mut self.frame, "P2", self.snake2.score.total(), col + 2, 0, colors[1]);
+        for (i, (label, snake)) in [("P1 died:", &self.snake), ("P2 died:", &self.snake2)].into_iter().enumerate() {
+            if let Some(death) = snake.stats.death {
+                let color = self.player_color(i);
+                self.frame.plot_str(label, col + 2, 0, color);
+                col += 2 + label.len() + 1;
+                self.frame.plot_str(death.cause().description(), col, 0, color);
+                col += death.cause().description().len();
+            }
         }
     }
     
-    fn get_icon_color(&mut self, p: Position<WIDTH,HEIGHT>, cell: &Cell) -> (char, ColorCode) {
-        let (icon, foreground) =
-            if p == self.snake.pos {
-                (match self.status() {
-                    Status::Over => 'X',
-                    Status::Over2 => 'X',
-                    _ => self.snake.icon()
-                }, Color::Blue)
-            } 
-            else if (p == self.snake2.pos) & self.two_player {
-                (match self.status() {
-                    Status::Over1 => 'X',
-                    _ => self.snake2.icon()
-                }, Color::Magenta)
-            } 
-            else {
-                match cell {
-                Cell::Body => ('o', Color::Blue),
-                Cell::Body2 => ('o', Color::Magenta),
-                Cell::Empty => (' ', Color::Black),
-                Cell::Wall => ('#', Color::Brown),
-                Cell::Food => ('@', Color::Red),
+    /// Draws `text` centred on the board in the pixel font, with every pixel
+    /// blown up to a block of cells.
+    fn draw_big_text(&mut self, text: &[char]) {
+        let glyph_width = 4 * BIG_PIXEL_WIDTH;
+        let width = text.len() * glyph_width - BIG_PIXEL_WIDTH;
+        let left = self.view().width.saturating_sub(width) / 2;
+        let top = HEADER_SPACE + self.view().height.saturating_sub(5 * BIG_PIXEL_HEIGHT) / 2;
+        let lit = ColorCode::new(self.settings.theme.highlight, self.settings.theme.highlight);
+        for (i, c) in text.iter().enumerate() {
+            for (y, bits) in font_glyph(*c).iter().enumerate() {
+                for x in (0..3).filter(|x| bits & (0b100 >> x) != 0) {
+                    for row in 0..BIG_PIXEL_HEIGHT {
+                        for col in 0..BIG_PIXEL_WIDTH {
+                            self.frame.plot(' ', left + i * glyph_width + x * BIG_PIXEL_WIDTH + col, top + y * BIG_PIXEL_HEIGHT + row, lit);
+                        }
+                    }
                 }
-            };
-        (icon, ColorCode::new(foreground, Color::Green))
+            }
+        }
     }
 
-    fn reset(&mut self, two: bool) {
-        if !two {
-            self.two_player = false;
-            for (row, row_chars) in START1.split('\n').enumerate() {
-                for (col, icon) in row_chars.trim().chars().enumerate() {
-                    self.translate_icon(row, col, icon);
-                }
+    fn draw_panel(&mut self, title: &str, width: usize, height: usize) -> (usize, usize) {
+        let frame = self.settings.theme.on_panel(self.settings.theme.panel_text);
+        let left = self.view().width.saturating_sub(width) / 2;
+        let top = HEADER_SPACE + self.view().height.saturating_sub(height) / 2;
+        for row in top..top + height {
+            for col in left..left + width {
+                let edge_row = row == top || row == top + height - 1;
+                let edge_col = col == left || col == left + width - 1;
+                let c = match (edge_row, edge_col) {
+                    (true, true) => '+',
+                    (true, false) => '-',
+                    (false, true) => '|',
+                    (false, false) => ' '
+                };
+                self.frame.plot(c, col, row, frame);
             }
         }
-        else {
-            self.two_player = true;
-            for (row, row_chars) in START2.split('\n').enumerate() {
-                for (col, icon) in row_chars.trim().chars().enumerate() {
-                    self.translate_icon(row, col, icon);
+        self.frame.plot_str(title, left + (width - title.len()) / 2, top + 1, frame);
+        (left, top)
+    }
+
+    fn draw_summary(&mut self) {
+        let frame = self.settings.theme.on_panel(self.settings.theme.panel_text);
+        let players = if self.two_player { 2 } else { 1 };
+        let width = SUMMARY_LABEL_WIDTH + players * SUMMARY_COLUMN_WIDTH + 4;
+        let (left, top) = self.draw_panel("Round Summary", width, SUMMARY_HEIGHT);
+        let labels = ["Final length", "Max length", "Food eaten", "Seconds alive", "Ticks alive", "Score", "Cause of death", "Died at"];
+        for (i, label) in labels.iter().enumerate() {
+            self.frame.plot_str(label, left + 2, top + 3 + i, frame);
+        }
+        let snakes = [(&self.snake, "Player 1"), (&self.snake2, "Player 2")];
+        for (i, (snake, name)) in snakes.iter().take(players).enumerate() {
+            let col = left + 2 + SUMMARY_LABEL_WIDTH + i * SUMMARY_COLUMN_WIDTH;
+            let color = self.settings.theme.on_panel(self.settings.players[i].color);
+            if self.two_player {
+                self.frame.plot_str(name, col, top + 2, color);
+            }
+            let values = [snake.size, snake.stats.max_length, snake.stats.food_eaten,
+                          snake.stats.ticks / TICKS_PER_SECOND, snake.stats.ticks, snake.score.total()];
+            for (row, value) in values.iter().enumerate() {
+                self.frame.plot_num(*value as isize, col, top + 3 + row, color);
+            }
+            let row = top + 3 + values.len();
+            match snake.stats.death {
+                Some(death) => {
+                    self.frame.plot_str(death.cause().description(), col, row, color);
+                    let (death_row, death_col) = (death.pos.row as isize, death.pos.col as isize);
+                    let col = self.frame.plot_num(death_row, col, row + 1, color);
+                    self.frame.plot(',', col, row + 1, color);
+                    self.frame.plot_num(death_col, col + 1, row + 1, color);
+                }
+                None => {
+                    self.frame.plot_str("-", col, row, color);
+                    self.frame.plot_str("-", col, row + 1, color);
                 }
             }
         }
-        self.status = Status::Normal;
-        self.last_key = None;
-        self.last_key2 = None;
     }
 
-    fn translate_icon(&mut self, row: usize, col: usize, icon: char) {
-        match icon {
-            '#' => self.cells[row][col] = Cell::Wall,
-            ' ' => self.cells[row][col] = Cell::Empty,
-            '@' => self.cells[row][col] = Cell::Food,
-            '>' | 'v' => {
-                self.snake = Snake::new(Position {row: row as i16, col: col as i16}, icon);
-            },
-            '<' | '^' => {
-                self.snake2 = Snake::new(Position {row: row as i16, col: col as i16}, icon);
-            },
-            _ =>  panic!("Unrecognized character: '{}'", icon)
+    fn draw_menu(&mut self) {
+        let frame = self.settings.theme.on_panel(self.settings.theme.panel_text);
+        let selected = self.settings.theme.on_panel(self.settings.players[0].color);
+        let width = SUMMARY_LABEL_WIDTH + 2 * SUMMARY_COLUMN_WIDTH;
+        let (left, top) = self.draw_panel("Main Menu", width, self.menu().count() + 4);
+        for (i, item) in self.menu().enumerate() {
+            let row = top + 2 + i;
+            let color = if i == self.menu_row { selected } else { frame };
+            if i == self.menu_row {
+                self.frame.plot('>', left + 1, row, color);
+            }
+            self.frame.plot_str(item.label(), left + 2, row, color);
+            self.draw_menu_value(item, left + 2 + SUMMARY_LABEL_WIDTH, row, color);
         }
     }
 
-    pub fn cell(&self, p: Position<WIDTH,HEIGHT>) -> Cell {
-        self.cells[p.row as usize][p.col as usize]
+    /// The menu items that do something with this renderer. Mode 13h has
+    /// no rows to change.
+    fn menu(&self) -> impl Iterator<Item = MenuItem> {
+        let text = self.renderer == Renderer::Text;
+        MENU.into_iter().filter(move |item| text || *item != MenuItem::Rows)
     }
 
-    pub fn cell_pos_iter(&self) -> RowColIter<WIDTH,HEIGHT> {
-        RowColIter { row: 0, col: 0 }
+    fn menu_item(&self) -> MenuItem {
+        self.menu().nth(self.menu_row).unwrap_or(MenuItem::Play)
     }
 
-    pub fn update(&mut self) {
-        self.resolve_move();
-        if self.two_player {
-            self.resolve_move2();
+    fn draw_menu_value(&mut self, item: MenuItem, col: usize, row: usize, color: ColorCode) {
+        let on_off = |on: bool| if on { "On" } else { "Off" };
+        let name = match item {
+            MenuItem::Mode => self.settings.mode.name(),
+            MenuItem::Level => self.settings.level.name(),
+            MenuItem::Difficulty if self.settings.level == Level::Classic => "-",
+            MenuItem::Fair => self.settings.symmetry.name(),
+            MenuItem::Edges => self.settings.edges.name(),
+            MenuItem::World => self.settings.world.name(),
+            MenuItem::Fog => on_off(self.settings.fog),
+            MenuItem::Sound => on_off(self.settings.sound),
+            MenuItem::Theme => self.settings.theme.name,
+            MenuItem::Rows => self.text_mode.name(),
+            MenuItem::Keyboard => self.settings.layout.name(),
+            MenuItem::Countdown if self.settings.countdown == 0 => "Off",
+            MenuItem::Difficulty | MenuItem::Speed | MenuItem::Countdown => "",
+            _ => return
+        };
+        let num = match item {
+            MenuItem::Difficulty => self.settings.difficulty,
+            MenuItem::Speed => self.settings.speed,
+            _ => self.settings.countdown
+        };
+        if name.is_empty() {
+            self.frame.plot_num(num as isize, col, row, color);
+        } else {
+            self.frame.plot_str(name, col, row, color);
         }
-        self.last_key = None;
-        self.last_key2 = None;
     }
 
-    pub fn key(&mut self, dkey: DecodedKey) {
-        match self.status {
-            Status::Normal => {
-                let key = key2dir(dkey);
-                if key.is_some() {
-                    self.last_key = key;
-                }
-                let key = key2dir2(dkey);
-                if key.is_some() {
-                    self.last_key2 = key;
-                }
-            }
-            _ => {
-                match dkey {
-                    DecodedKey::RawKey(KeyCode::Key1) | DecodedKey::Unicode('1') => self.reset(false),
-                    DecodedKey::RawKey(KeyCode::Key2) | DecodedKey::Unicode('2') => self.reset(true),
-                    _ => {}
+    fn draw_controls(&mut self) {
+        let frame = self.settings.theme.on_panel(self.settings.theme.panel_text);
+        let width = SUMMARY_LABEL_WIDTH + 2 * SUMMARY_COLUMN_WIDTH + 8;
+        let (left, top) = self.draw_panel("Controls", width, SETUP_HEIGHT + 2);
+        let (cursor_row, cursor_player) = self.controls_cursor;
+        for (i, name) in DIR_NAMES.iter().enumerate() {
+            self.frame.plot_str(name, left + 2, top + 3 + i, frame);
+        }
+        for (player, name) in ["Player 1", "Player 2"].iter().enumerate() {
+            let col = left + 2 + SUMMARY_LABEL_WIDTH / 2 + player * (SUMMARY_COLUMN_WIDTH + 4);
+            let color = self.settings.theme.on_panel(self.settings.players[player].color);
+            self.frame.plot_str(name, col, top + 2, color);
+            for (row, binding) in self.settings.keys[player].bindings.iter().enumerate() {
+                let selected = (row, player) == (cursor_row, cursor_player);
+                if selected {
+                    self.frame.plot('>', col - 1, top + 3 + row, frame);
                 }
+                let mut buf = [0; 4];
+                let name = if selected && self.rebinding { "?" } else { binding.name(&mut buf) };
+                self.frame.plot_str(name, col, top + 3 + row, color);
             }
         }
+        let defaults_row = top + 3 + DIRS.len();
+        if cursor_row == DIRS.len() {
+            self.frame.plot('>', left + 1, defaults_row, frame);
+        }
+        self.frame.plot_str("Restore defaults", left + 2, defaults_row, frame);
+        self.frame.plot_str("Tab toggles the minimap during play", left + 2, defaults_row + 2, frame);
     }
 
-    pub fn countdown_complete(&mut self) -> bool {
-        if self.countdown == 0 {
-            self.countdown = UPDATE_FREQUENCY;
-            true
-        } else {
-            self.countdown -= 1;
-            false
+    fn draw_high_scores(&mut self) {
+        let frame = self.settings.theme.on_panel(self.settings.theme.panel_text);
+        let width = 8 + MODES.len() * SUMMARY_COLUMN_WIDTH;
+        let (left, top) = self.draw_panel("High Scores", width, HIGH_SCORES + 5);
+        for (i, mode) in MODES.iter().enumerate() {
+            let col = left + 6 + i * SUMMARY_COLUMN_WIDTH;
+            self.frame.plot_str(mode.name(), col, top + 2, frame);
+            for (rank, score) in self.high_scores[i].iter().enumerate() {
+                self.frame.plot_num(*score as isize, col, top + 3 + rank, frame);
+            }
+        }
+        for rank in 0..HIGH_SCORES {
+            self.frame.plot('#', left + 2, top + 3 + rank, frame);
+            self.frame.plot_num(rank as isize + 1, left + 3, top + 3 + rank, frame);
         }
     }
 
-    fn resolve_move(&mut self) {
-        if let Some(dir) = self.last_key {
-            if dir != self.snake.dir.reverse() {
-                self.snake.dir = dir;
+    fn draw_setup(&mut self) {
+        let frame = self.settings.theme.on_panel(self.settings.theme.panel_text);
+        let width = SUMMARY_LABEL_WIDTH + 2 * SUMMARY_COLUMN_WIDTH + 4;
+        let (left, top) = self.draw_panel("Player Setup", width, SETUP_HEIGHT);
+        for (i, label) in ["Head", "Body", "Colour", "", "Preview"].iter().enumerate() {
+            self.frame.plot_str(label, left + 2, top + 3 + i, frame);
+        }
+        for (i, name) in ["Player 1", "Player 2"].iter().enumerate() {
+            let style = self.settings.players[i];
+            let col = left + 2 + SUMMARY_LABEL_WIDTH + i * SUMMARY_COLUMN_WIDTH;
+            let color = self.settings.theme.on_panel(style.color);
+            self.frame.plot_str(name, col, top + 2, color);
+            for (row, value) in [style.head.name(), style.body.name(), color_name(style.color)].iter().enumerate() {
+                let marker = if self.setup_row[i] == row { '>' } else { ' ' };
+                self.frame.plot(marker, col - 1, top + 3 + row, frame);
+                self.frame.plot_str(value, col, top + 3 + row, color);
+            }
+            let preview = self.settings.theme.header(style.color);
+            let segments = [self.body_glyph(i, '\u{F9}'), self.body_glyph(i, '\u{C4}'), self.body_glyph(i, '\u{C4}'), self.head_glyph(i, Dir::E)];
+            for (j, glyph) in segments.iter().enumerate() {
+                self.frame.plot(*glyph, col + j, top + 7, preview);
             }
         }
-        let dir = self.snake.dir;
-        let neighbor = self.snake.pos.neighbor(dir);
-        if neighbor.is_legal() {
-            let (row, col) = neighbor.row_col();
-            if (self.cells[row][col] == Cell::Body) | (self.cells[row][col] == Cell::Body2) | (self.cells[row][col] == Cell::Wall) {
-                if self.two_player {
-                    self.status = Status::Over2;
-                } else {   
-                    self.status = Status::Over
+    }
+
+    /// Draws the part of the world under each camera; anything past the
+    /// world's edges is blank.
+    fn draw_board(&mut self) {
+        let void = ColorCode::new(Color::Black, Color::Black);
+        for i in 0..self.viewport_count() {
+            let (left, view) = self.viewport(i);
+            let camera = self.cameras[i];
+            for row in 0..view.height {
+                for col in 0..view.width {
+                    let p = Position {row: camera.row + row as i16, col: camera.col + col as i16};
+                    if self.dims.contains(p) {
+                        let (c, color) = self.get_icon_color(p, &self.cell(p));
+                        self.frame.plot(c, left + col, row + HEADER_SPACE, color);
+                    } else {
+                        self.frame.plot(' ', left + col, row + HEADER_SPACE, void);
+                    }
                 }
             }
-            else if self.status == Status::Normal {
-                self.move_to(neighbor, dir);
+        }
+        if self.is_split() {
+            let divider = self.settings.theme.header(self.settings.theme.text);
+            let (left, _) = self.viewport(1);
+            for row in 0..self.view().height {
+                self.frame.plot('\u{BA}', left - 1, row + HEADER_SPACE, divider);
             }
         }
     }
 
-    fn update_snake_body(&mut self, new_body: Position<WIDTH,HEIGHT>, grow:bool) {
-        self.snake.body[self.snake.insert_index] = new_body;
-        self.snake.insert_index += 1;
-        if self.snake.insert_index == ARRAY_SIZE {
-            self.snake.insert_index = 0;
+    /// The whole world shrunk into the top right corner of the board, framed
+    /// in the panel colour. Each character stands for a block of cells and
+    /// shows the most important thing in it: a head, then food, then a body,
+    /// then a wall.
+    fn draw_minimap(&mut self) {
+        if !self.minimap || !self.is_scrolling() {
+            return;
         }
-        if !grow {
-            let cleared_pos = self.snake.body[self.snake.remove_index];
-            self.snake.remove_index += 1;
-            if self.snake.remove_index == ARRAY_SIZE {
-                self.snake.remove_index = 0;
+        let block_width = self.dims.width.div_ceil(MINIMAP_WIDTH);
+        let block_height = self.dims.height.div_ceil(MINIMAP_HEIGHT);
+        let width = self.dims.width.div_ceil(block_width);
+        let height = self.dims.height.div_ceil(block_height);
+        let left = BUFFER_WIDTH - width - 1;
+        let top = HEADER_SPACE;
+        let border = ColorCode::new(self.settings.theme.panel_text, self.settings.theme.panel);
+        for row in 0..height + 2 {
+            self.frame.plot(' ', left - 1, top + row, border);
+            self.frame.plot(' ', left + width, top + row, border);
+        }
+        for col in 0..width {
+            self.frame.plot(' ', left + col, top, border);
+            self.frame.plot(' ', left + col, top + height + 1, border);
+        }
+        for row in 0..height {
+            for col in 0..width {
+                let (c, color) = self.minimap_block(row * block_height, col * block_width, block_height, block_width);
+                self.frame.plot(c, left + col, top + row + 1, color);
             }
-            self.cells[cleared_pos.row as usize][cleared_pos.col as usize] = Cell::Empty;
         }
     }
 
-    fn move_to(&mut self, neighbor: Position<WIDTH,HEIGHT>, dir: Dir) {
-        let curr_pos = self.snake.pos;
-        self.cells[curr_pos.row as usize][curr_pos.col as usize] = Cell::Body;
-        self.snake.pos = neighbor;
-        self.snake.dir = dir;
-        let (row, col) = neighbor.row_col();
-        match self.cells[row][col] {
-            Cell::Food => {
+    fn minimap_block(&self, top: usize, left: usize, height: usize, width: usize) -> (char, ColorCode) {
+        let background = self.settings.theme.background;
+        let mut shown = (' ', ColorCode::new(background, background));
+        let mut rank = 0;
+        let cells = (top..top + height).flat_map(|row| (left..left + width).map(move |col| Position {row: row as i16, col: col as i16}));
+        for p in cells.filter(|p| self.dims.contains(*p)) {
+            let (this_rank, c, foreground, back) = if self.in_fog(p) {
+                match self.cell(p) {
+                    Cell::Wall if self.seen[p.row as usize][p.col as usize] => (1, ' ', background, Color::DarkGray),
+                    _ => (0, ' ', background, background)
+                }
+            } else if p == self.snake.pos {
+                (4, '@', self.settings.theme.panel_text, self.settings.players[0].color)
+            } else if self.two_player && p == self.snake2.pos {
+                (4, '@', self.settings.theme.panel_text, self.settings.players[1].color)
+            } else {
+                match self.cell(p) {
+                    Cell::Food => (3, '*', self.settings.theme.food.1, background),
+                    Cell::Body => (2, ' ', background, self.settings.players[0].color),
+                    Cell::Body2 => (2, ' ', background, self.settings.players[1].color),
+                    Cell::Wall => (1, ' ', background, self.settings.theme.wall.1),
+                    Cell::Empty => (0, ' ', background, background)
+                }
+            };
+            if this_rank > rank {
+                rank = this_rank;
+                shown = (c, ColorCode::new(foreground, back));
+            }
+        }
+        shown
+    }
+
+    /// Redraws `p` in every viewport that shows it.
+    fn draw_cell(&mut self, p: Position<WIDTH,HEIGHT>) {
+        let (c, color) = self.get_icon_color(p, &self.cell(p));
+        for i in 0..self.viewport_count() {
+            let (left, view) = self.viewport(i);
+            let (row, col) = (p.row - self.cameras[i].row, p.col - self.cameras[i].col);
+            if row >= 0 && col >= 0 && (row as usize) < view.height && (col as usize) < view.width {
+                self.frame.plot(c, left + col as usize, row as usize + HEADER_SPACE, color);
+            }
+        }
+    }
+    
+    fn get_icon_color(&self, p: Position<WIDTH,HEIGHT>, cell: &Cell) -> (char, ColorCode) {
+        if self.in_fog(p) {
+            return if self.seen[p.row as usize][p.col as usize] && *cell == Cell::Wall {
+                (self.tile_glyph(Tile::Wall, self.settings.theme.wall.0), ColorCode::new(Color::DarkGray, Color::Black))
+            } else {
+                (' ', ColorCode::new(Color::Black, Color::Black))
+            };
+        }
+        let (icon, foreground) =
+            if p == self.snake.pos {
+                (match self.status() {
+                    Status::Over | Status::Over2 | Status::Draw => 'X',
+                    _ if self.invulnerable % 2 == 1 => ' ',
+                    _ => self.head_glyph(0, self.snake.dir)
+                }, self.settings.players[0].color)
+            } 
+            else if (p == self.snake2.pos) & self.two_player {
+                (match self.status() {
+                    Status::Over1 | Status::Draw => 'X',
+                    _ => self.head_glyph(1, self.snake2.dir)
+                }, self.settings.players[1].color)
+            } 
+            else {
+                match cell {
+                Cell::Body => self.body_icon(p, 0),
+                Cell::Body2 => self.body_icon(p, 1),
+                Cell::Empty => (' ', self.settings.theme.text),
+                Cell::Wall => (self.tile_glyph(Tile::Wall, self.settings.theme.wall.0), self.settings.theme.wall.1),
+                Cell::Food => (self.tile_glyph(Tile::Food, self.settings.theme.food.0), self.settings.theme.food.1),
+                }
+            };
+        let (icon, foreground) = self.animate_icon(p, *cell, icon, foreground);
+        let background = if self.is_collision(p) { self.settings.theme.collision } else { self.settings.theme.background };
+        (icon, ColorCode::new(foreground, background))
+    }
+
+    /// Overlays whichever animations cover `p` on the glyph it would
+    /// otherwise show. A dead snake's body stays crumbled once its
+    /// animation has finished.
+    fn animate_icon(&self, p: Position<WIDTH,HEIGHT>, cell: Cell, icon: char, foreground: Color) -> (char, Color) {
+        let now = self.total_ticks;
+        let mut shown = (icon, foreground);
+        for a in self.animations.iter() {
+            let frame = a.frame(now);
+            match a.effect {
+                Effect::FadeIn if a.pos == p && cell == Cell::Food => match frame * 3 / a.effect.ticks() {
+                    0 => shown = ('.', Color::DarkGray),
+                    1 => shown = ('+', foreground),
+                    _ => {}
+                },
+                Effect::Sparkle if cell == Cell::Empty && p != a.pos
+                    && p.row.abs_diff(a.pos.row) <= 1 && p.col.abs_diff(a.pos.col) <= 1 => {
+                    shown = (if frame % 2 == 0 { '*' } else { '+' }, self.settings.theme.highlight);
+                }
+                _ => {}
+            }
+        }
+        let player = match cell {
+            Cell::Body => 0,
+            Cell::Body2 => 1,
+            _ => return shown
+        };
+        if !self.is_dead(player) {
+            return shown;
+        }
+        let snake = [&self.snake, &self.snake2][player];
+        let frame = self.animations.iter()
+            .find(|a| a.effect == Effect::Death(player))
+            .map_or(usize::MAX, |a| a.frame(now));
+        let crumble = FLASH_TICKS + (p.row.abs_diff(snake.pos.row) + p.col.abs_diff(snake.pos.col)) as usize / 2;
+        if frame < FLASH_TICKS {
+            if (frame / 3) % 2 == 1 { (icon, self.settings.theme.collision) } else { shown }
+        } else if frame < crumble {
+            shown
+        } else if frame < crumble + 4 {
+            (':', foreground)
+        } else {
+            ('.', Color::DarkGray)
+        }
+    }
+
+    /// Whether fog of war hides `p`: only cells near a live head can be seen,
+    /// and only while a round is being played or counted down.
+    fn in_fog(&self, p: Position<WIDTH,HEIGHT>) -> bool {
+        self.settings.fog && matches!(self.status, Status::Normal | Status::Countdown) && !self.is_visible(p)
+    }
+
+    /// Cells count double vertically since they are twice as tall as wide.
+    fn is_visible(&self, p: Position<WIDTH,HEIGHT>) -> bool {
+        self.heads().any(|head| {
+            let (rows, cols) = (2 * (p.row - head.row) as i32, (p.col - head.col) as i32);
+            rows * rows + cols * cols <= FOG_RADIUS * FOG_RADIUS
+        })
+    }
+
+    fn heads(&self) -> impl Iterator<Item = Position<WIDTH,HEIGHT>> {
+        [self.snake.pos, self.snake2.pos].into_iter().take(if self.two_player { 2 } else { 1 })
+    }
+
+    /// Remembers every cell currently in sight so that its walls stay on
+    /// screen, dimmed, once the snakes move on.
+    fn look(&mut self) {
+        for head in self.heads() {
+            for row in head.row - FOG_RADIUS as i16 / 2..=head.row + FOG_RADIUS as i16 / 2 {
+                for col in head.col - FOG_RADIUS as i16..=head.col + FOG_RADIUS as i16 {
+                    let p = Position {row, col};
+                    if self.dims.contains(p) && self.is_visible(p) {
+                        self.seen[row as usize][col as usize] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    fn body_icon(&self, p: Position<WIDTH,HEIGHT>, player: usize) -> (char, Color) {
+        (self.body_glyph(player, self.glyphs[p.row as usize][p.col as usize] as char), self.settings.players[player].color)
+    }
+
+    fn player_color(&self, player: usize) -> ColorCode {
+        self.settings.theme.header(self.settings.players[player].color)
+    }
+
+    fn head_glyph(&self, player: usize, dir: Dir) -> char {
+        self.tile_glyph(Tile::Head(dir), self.settings.players[player].head(dir))
+    }
+
+    fn body_glyph(&self, player: usize, glyph: char) -> char {
+        self.tile_glyph(Tile::body(glyph), self.settings.players[player].body(glyph))
+    }
+
+    fn tile_glyph(&self, tile: Tile, text: char) -> char {
+        match self.renderer {
+            Renderer::Text => text,
+            Renderer::Pixels => tile.glyph()
+        }
+    }
+
+    fn is_collision(&self, p: Position<WIDTH,HEIGHT>) -> bool {
+        self.is_over() && [self.snake.stats.death, self.snake2.stats.death].iter()
+            .any(|death| death.is_some_and(|d| d.pos == p))
+    }
+
+    /// Starts a round with the menu's settings.
+    fn reset(&mut self) {
+        let lives = if self.settings.mode == Mode::Lives { STARTING_LIVES } else { 1 };
+        self.dims = self.world_dims();
+        self.load_board(self.settings.mode.two_player());
+        self.update_frequency = SLOWEST_UPDATE_FREQUENCY + 1 - self.settings.speed;
+        self.redraw_all = true;
+        self.last_key = None;
+        self.last_key2 = None;
+        self.lives = lives;
+        self.starting_lives = lives;
+        self.invulnerable = 0;
+        self.start_clock();
+        self.snake.stats = Stats::default();
+        self.snake2.stats = Stats::default();
+        self.starting = self.settings.countdown * TICKS_PER_SECOND + GO_TICKS;
+        self.status = if self.settings.countdown == 0 { Status::Normal } else { Status::Countdown };
+        if self.status == Status::Countdown {
+            self.play(Sound::Count);
+        }
+    }
+
+    /// Scores count from here, so the countdown doesn't earn survival points.
+    fn start_clock(&mut self) {
+        self.round_start = self.total_ticks;
+        self.snake.score = Score::new(self.round_start);
+        self.snake2.score = Score::new(self.round_start);
+    }
+
+    fn load_board(&mut self, two: bool) {
+        self.two_player = two;
+        self.cameras = [Position {row: 0, col: 0}; 2];
+        match self.settings.level {
+            Level::Classic => self.load_map(if two { START2 } else { START1 }),
+            Level::Generated(layout) => {
+                let spec = LevelSpec {
+                    layout,
+                    seed: self.settings.seed.unwrap_or(self.total_ticks as u64),
+                    difficulty: self.settings.difficulty,
+                    players: if two { 2 } else { 1 },
+                    symmetry: self.settings.symmetry,
+                    dims: self.dims
+                };
+                self.load_level(&generate(&spec));
+            }
+        }
+        if self.settings.edges == Edges::Wrap {
+            self.open_edges();
+        }
+        self.follow();
+        self.animations.clear();
+        self.seen = [[false; WIDTH]; HEIGHT];
+        self.look();
+    }
+
+    /// Clears the walls around the outside so that snakes can wrap.
+    fn open_edges(&mut self) {
+        let (width, height) = (self.dims.width, self.dims.height);
+        for p in self.cell_pos_iter() {
+            let (row, col) = p.row_col();
+            if (row == 0 || col == 0 || row == height - 1 || col == width - 1) && self.cell(p) == Cell::Wall {
                 self.cells[row][col] = Cell::Empty;
-                self.snake.size += 1;
-                self.new_food();
-                self.update_snake_body(curr_pos, true);
             }
-            _ => {self.update_snake_body(curr_pos, false);}
         }
     }
 
-    fn resolve_move2(&mut self) {
+    /// Fits `map` to the board: past the map's interior, rows and columns
+    /// repeat its last interior row and column, and its final row and column
+    /// become the far edges. Everything beyond the board is wall.
+    fn load_map(&mut self, map: &str) {
+        self.cells = [[Cell::Wall; WIDTH]; HEIGHT];
+        let rows = map.split('\n').count();
+        for row in 0..self.dims.height {
+            let row_chars = map.split('\n').nth(stretch(row, self.dims.height, rows)).unwrap_or("").trim();
+            let cols = row_chars.chars().count();
+            for col in 0..self.dims.width {
+                if let Some(icon) = row_chars.chars().nth(stretch(col, self.dims.width, cols)) {
+                    self.translate_icon(row, col, icon);
+                }
+            }
+        }
+    }
+
+    fn load_level(&mut self, level: &GeneratedLevel<WIDTH,HEIGHT>) {
+        self.cells = level.cells;
+        let (pos, dir) = level.spawns[0];
+        self.spawn = pos;
+        self.spawn_icon = dir.icon();
+        self.snake = Snake::new(pos, self.spawn_icon);
+        let (pos, dir) = level.spawns[1];
+        self.snake2 = Snake::new(pos, dir.icon());
+        self.new_food();
+    }
+
+    fn translate_icon(&mut self, row: usize, col: usize, icon: char) {
+        match icon {
+            '#' => self.cells[row][col] = Cell::Wall,
+            ' ' => self.cells[row][col] = Cell::Empty,
+            '@' => self.cells[row][col] = Cell::Food,
+            '>' | 'v' => {
+                self.cells[row][col] = Cell::Empty;
+                self.spawn = Position {row: row as i16, col: col as i16};
+                self.spawn_icon = icon;
+                self.snake = Snake::new(self.spawn, icon);
+            },
+            '<' | '^' => {
+                self.cells[row][col] = Cell::Empty;
+                self.snake2 = Snake::new(Position {row: row as i16, col: col as i16}, icon);
+            },
+            _ =>  panic!("Unrecognized character: '{}'", icon)
+        }
+    }
+
+    pub fn cell(&self, p: Position<WIDTH,HEIGHT>) -> Cell {
+        self.cells[p.row as usize][p.col as usize]
+    }
+
+    pub fn cell_pos_iter(&self) -> RowColIter<WIDTH,HEIGHT> {
+        RowColIter { row: 0, col: 0, width: self.dims.width, height: self.dims.height }
+    }
+
+    pub fn dims(&self) -> Dimensions {
+        self.dims
+    }
+
+    pub fn update(&mut self) {
+        if self.status != Status::Normal {
+            return;
+        }
+        let header = self.header_state();
+        let survived = self.total_ticks.wrapping_sub(self.round_start);
+        for snake in [&mut self.snake, &mut self.snake2] {
+            snake.score.survive(survived);
+            snake.stats.ticks = survived;
+        }
+        self.steer();
+        if self.two_player && self.heads_collide() {
+            self.record_death(0, Cause::HeadOn, self.ahead(self.snake.pos, self.snake.dir));
+            self.record_death(1, Cause::HeadOn, self.ahead(self.snake2.pos, self.snake2.dir));
+            self.status = Status::Draw;
+        } else {
+            self.resolve_move();
+            if self.two_player && self.status != Status::Cleared {
+                self.resolve_move2();
+            }
+        }
+        for player in 0..2 {
+            if self.is_dead(player) {
+                let pos = [self.snake.pos, self.snake2.pos][player];
+                self.animations.start(Effect::Death(player), pos, self.total_ticks);
+                self.play(Sound::Death);
+            }
+        }
+        if self.is_over() {
+            self.record_scores();
+        }
+        self.follow();
+        if self.settings.fog {
+            self.look();
+            self.redraw_all = true;
+        }
+        if self.header_state() != header {
+            self.redraw_header = true;
+        }
+        self.last_key = None;
+        self.last_key2 = None;
+    }
+
+    fn record_scores(&mut self) {
+        let table = &mut self.high_scores[self.settings.mode.index()];
+        menu::record(table, self.snake.score.total());
+        if self.two_player {
+            menu::record(table, self.snake2.score.total());
+        }
+    }
+
+    /// Whether `player` has died and ended the game; earlier lives don't count.
+    fn is_dead(&self, player: usize) -> bool {
+        let snake = [&self.snake, &self.snake2][player];
+        self.is_over() && self.status != Status::Cleared && snake.stats.death.is_some()
+    }
+
+    fn is_over(&self) -> bool {
+        matches!(self.status, Status::Over | Status::Over1 | Status::Over2 | Status::Draw | Status::Cleared)
+    }
+
+    fn steer(&mut self) {
+        if let Some(dir) = self.last_key {
+            if self.invulnerable > 0 || dir != self.snake.dir.reverse() {
+                self.snake.dir = dir;
+            }
+        }
         if let Some(dir) = self.last_key2 {
             if dir != self.snake2.dir.reverse() {
                 self.snake2.dir = dir;
             }
         }
-        let dir = self.snake2.dir;
-        let neighbor = self.snake2.pos.neighbor(dir);
-        if neighbor.is_legal() {
-            let (row, col) = neighbor.row_col();
-            if (self.cells[row][col] == Cell::Body) | (self.cells[row][col] == Cell::Body2) | (self.cells[row][col] == Cell::Wall){
-                self.status = Status::Over1
+    }
+
+    /// The cell `dir` leads to from `p`, coming back on the far side of the
+    /// board when the edges wrap.
+    fn ahead(&self, p: Position<WIDTH,HEIGHT>, dir: Dir) -> Position<WIDTH,HEIGHT> {
+        let next = p.neighbor(dir);
+        match self.settings.edges {
+            Edges::Walls => next,
+            Edges::Wrap => Position {
+                row: next.row.rem_euclid(self.dims.height as i16),
+                col: next.col.rem_euclid(self.dims.width as i16)
             }
-            else if self.status == Status::Normal {
-                self.move_to2(neighbor, dir);
+        }
+    }
+
+    fn heads_collide(&self) -> bool {
+        let next = self.ahead(self.snake.pos, self.snake.dir);
+        let next2 = self.ahead(self.snake2.pos, self.snake2.dir);
+        next == next2 || (next == self.snake2.pos && next2 == self.snake.pos)
+    }
+
+    fn collision(&self, p: Position<WIDTH,HEIGHT>, own: Cell, other_head: Option<Position<WIDTH,HEIGHT>>) -> Option<Cause> {
+        if !self.dims.contains(p) {
+            return Some(Cause::Boundary);
+        }
+        if other_head == Some(p) {
+            return Some(Cause::Opponent);
+        }
+        match self.cell(p) {
+            Cell::Wall => Some(Cause::Wall),
+            c if c == own => Some(Cause::OwnBody),
+            Cell::Body | Cell::Body2 => Some(Cause::Opponent),
+            Cell::Empty | Cell::Food => None
+        }
+    }
+
+    pub fn death(&self) -> Option<Death<WIDTH,HEIGHT>> {
+        self.snake.stats.death
+    }
+
+    pub fn death2(&self) -> Option<Death<WIDTH,HEIGHT>> {
+        self.snake2.stats.death
+    }
+
+    pub fn key(&mut self, dkey: DecodedKey) {
+        let dkey = self.settings.layout.translate(dkey);
+        match self.status {
+            Status::Setup => self.setup_key(dkey),
+            Status::Exited => self.resume(),
+            Status::Countdown => {
+                if let DecodedKey::Unicode('\u{1b}') = dkey {
+                    self.status = Status::Start;
+                }
+                self.preselect(dkey);
+            }
+            Status::Normal => {
+                if let DecodedKey::Unicode('\t') = dkey {
+                    self.toggle_minimap();
+                }
+                let key = self.settings.keys[0].dir(dkey);
+                if key.is_some() {
+                    self.last_key = key;
+                }
+                let key = self.settings.keys[1].dir(dkey);
+                if key.is_some() {
+                    self.last_key2 = key;
+                }
+            }
+            Status::Start => self.menu_key(dkey),
+            Status::Controls => self.controls_key(dkey),
+            Status::HighScores => {
+                if let DecodedKey::Unicode('\n' | '\u{1b}') = dkey {
+                    self.status = Status::Start;
+                }
+            }
+            Status::Over | Status::Over1 | Status::Over2 | Status::Draw | Status::Cleared => {
+                if let DecodedKey::Unicode('\n' | '\u{1b}') = dkey {
+                    self.status = Status::Start;
+                } else {
+                    self.quick_start(dkey);
+                }
+            }
+        }
+    }
+
+    /// Number keys start a round in that mode straight away.
+    fn quick_start(&mut self, dkey: DecodedKey) {
+        let mode = match dkey {
+            DecodedKey::RawKey(KeyCode::Key1) | DecodedKey::Unicode('1') => Mode::Solo,
+            DecodedKey::RawKey(KeyCode::Key2) | DecodedKey::Unicode('2') => Mode::Duel,
+            DecodedKey::RawKey(KeyCode::Key3) | DecodedKey::Unicode('3') => Mode::Lives,
+            _ => return
+        };
+        self.settings.mode = mode;
+        self.reset();
+    }
+
+    /// Either player's up and down keys move through the menu, left and
+    /// right change the value under the cursor, and Enter picks it.
+    fn menu_key(&mut self, dkey: DecodedKey) {
+        match dkey {
+            DecodedKey::Unicode('\n') => self.select(self.menu_item()),
+            DecodedKey::Unicode('\u{1b}') => self.exit(),
+            _ => match self.either_dir(dkey) {
+                Some(Dir::N) => self.menu_row = (self.menu_row + self.menu().count() - 1) % self.menu().count(),
+                Some(Dir::S) => self.menu_row = (self.menu_row + 1) % self.menu().count(),
+                Some(dir) => self.change(self.menu_item(), dir == Dir::E),
+                None => return self.quick_start(dkey)
+            }
+        }
+        self.redraw_all = true;
+    }
+
+    /// Menus answer to either player's direction keys.
+    fn either_dir(&self, dkey: DecodedKey) -> Option<Dir> {
+        self.settings.keys[0].dir(dkey).or(self.settings.keys[1].dir(dkey))
+    }
+
+    /// The cursor moves over a grid of directions by player, with a last
+    /// row that restores the default keys. While rebinding, the next key
+    /// pressed other than Esc takes the slot under the cursor.
+    fn controls_key(&mut self, dkey: DecodedKey) {
+        let (row, player) = self.controls_cursor;
+        if self.rebinding {
+            let key = Binding::of(dkey);
+            if !key.is_reserved() {
+                rebind(&mut self.settings.keys, player, row, key);
+            }
+            if !key.is_reserved() || key == Binding::Char('\u{1b}') {
+                self.rebinding = false;
+            }
+        } else {
+            match dkey {
+                DecodedKey::Unicode('\u{1b}') => self.status = Status::Start,
+                DecodedKey::Unicode('\n') if row == DIRS.len() => self.settings.keys = [self.settings.layout.player1_keys(), PLAYER2_KEYS],
+                DecodedKey::Unicode('\n') => self.rebinding = true,
+                _ => match self.either_dir(dkey) {
+                    Some(Dir::N) => self.controls_cursor.0 = (row + CONTROLS_ROWS - 1) % CONTROLS_ROWS,
+                    Some(Dir::S) => self.controls_cursor.0 = (row + 1) % CONTROLS_ROWS,
+                    Some(_) => self.controls_cursor.1 = 1 - player,
+                    None => return
+                }
             }
         }
+        self.redraw_all = true;
     }
 
-    fn update_snake_body2(&mut self, new_body: Position<WIDTH,HEIGHT>, grow:bool) {
-        self.snake2.body[self.snake2.insert_index] = new_body;
-        self.snake2.insert_index += 1;
-        if self.snake2.insert_index == ARRAY_SIZE {
-            self.snake2.insert_index = 0;
+    fn select(&mut self, item: MenuItem) {
+        match item {
+            MenuItem::Play => self.reset(),
+            MenuItem::Players => self.status = Status::Setup,
+            MenuItem::Controls => self.status = Status::Controls,
+            MenuItem::HighScores => self.status = Status::HighScores,
+            MenuItem::Quit => self.exit(),
+            _ => self.change(item, true)
         }
-        if !grow {
-            let cleared_pos = self.snake2.body[self.snake2.remove_index];
-            self.snake2.remove_index += 1;
-            if self.snake2.remove_index == ARRAY_SIZE {
-                self.snake2.remove_index = 0;
+    }
+
+    /// Steps the setting under `item`. Anything that shapes the board
+    /// reloads the preview behind the menu.
+    fn change(&mut self, item: MenuItem, forward: bool) {
+        let settings = &mut self.settings;
+        match item {
+            MenuItem::Mode => settings.mode = cycle(&MODES, settings.mode, forward, |_| false),
+            MenuItem::Level => settings.level = cycle(&LEVELS, settings.level, forward, |_| false),
+            MenuItem::Difficulty => settings.difficulty = menu::step(settings.difficulty, MAX_DIFFICULTY, forward),
+            MenuItem::Fair => settings.symmetry = cycle(&SYMMETRIES, settings.symmetry, forward, |_| false),
+            MenuItem::Speed => settings.speed = menu::step(settings.speed, MAX_SPEED, forward),
+            MenuItem::Edges => settings.edges = cycle(&EDGES, settings.edges, forward, |_| false),
+            MenuItem::World => settings.world = cycle(&WORLDS, settings.world, forward, |_| false),
+            MenuItem::Fog => settings.fog = !settings.fog,
+            MenuItem::Countdown => settings.countdown = menu::step_from_zero(settings.countdown, MAX_COUNTDOWN, forward),
+            MenuItem::Sound => settings.sound = !settings.sound,
+            MenuItem::Theme => {
+                let theme = cycle(&THEMES, settings.theme, forward, |_| false);
+                for (style, (old, new)) in settings.players.iter_mut().zip(settings.theme.players.into_iter().zip(theme.players)) {
+                    if *style == old {
+                        *style = new;
+                    }
+                }
+                settings.theme = theme;
+                self.settle_styles();
+            }
+            MenuItem::Rows => self.switch_text_mode(self.text_mode.next()),
+            MenuItem::Keyboard => {
+                let old = settings.layout;
+                settings.layout = cycle(&LAYOUTS, old, forward, |_| false);
+                if settings.keys[0] == old.player1_keys() {
+                    settings.keys[0] = settings.layout.player1_keys();
+                }
+            }
+            _ => {}
+        }
+        if matches!(item, MenuItem::Mode | MenuItem::Level | MenuItem::Difficulty | MenuItem::Fair | MenuItem::Edges | MenuItem::World) {
+            self.dims = self.world_dims();
+            self.load_board(self.settings.mode.two_player());
+        }
+    }
+
+    /// Points a waiting snake in its starting direction. With no body yet
+    /// it may face any way, including straight back.
+    fn preselect(&mut self, dkey: DecodedKey) {
+        if let Some(dir) = self.settings.keys[0].dir(dkey) {
+            self.snake.dir = dir;
+            self.redraw_all = true;
+        }
+        if let Some(dir) = self.settings.keys[1].dir(dkey) {
+            if self.two_player {
+                self.snake2.dir = dir;
+                self.redraw_all = true;
+            }
+        }
+    }
+
+    fn setup_key(&mut self, dkey: DecodedKey) {
+        let (player, key) = match dkey {
+            DecodedKey::Unicode('\n') | DecodedKey::Unicode('\u{1b}') => {
+                self.status = Status::Start;
+                return;
+            }
+            _ => match self.settings.keys[0].dir(dkey) {
+                Some(dir) => (0, Some(dir)),
+                None => (1, self.settings.keys[1].dir(dkey))
+            }
+        };
+        match key {
+            Some(Dir::N) => self.setup_row[player] = (self.setup_row[player] + SETUP_ROWS - 1) % SETUP_ROWS,
+            Some(Dir::S) => self.setup_row[player] = (self.setup_row[player] + 1) % SETUP_ROWS,
+            Some(dir) => self.change_style(player, dir == Dir::E),
+            None => return
+        }
+        self.redraw_all = true;
+    }
+
+    fn change_style(&mut self, player: usize, forward: bool) {
+        let style = self.settings.players[player];
+        self.settings.players[player] = match self.setup_row[player] {
+            0 => PlayerStyle {head: cycle(&HEADS, style.head, forward, |head| self.rejects(player, PlayerStyle {head, ..style})), ..style},
+            1 => PlayerStyle {body: cycle(&BODIES, style.body, forward, |body| self.rejects(player, PlayerStyle {body, ..style})), ..style},
+            _ => PlayerStyle {color: cycle(&PALETTE, style.color, forward, |color| self.rejects(player, PlayerStyle {color, ..style})), ..style}
+        };
+    }
+
+    /// Players may share a head or a body glyph but not both, and never a
+    /// colour, and no one may pick the background colour.
+    fn rejects(&self, player: usize, style: PlayerStyle) -> bool {
+        let other = self.settings.players[1 - player];
+        style.color == other.color || style.color == self.settings.theme.background
+            || (style.head == other.head && style.body == other.body)
+    }
+
+    /// Moves any player whose style a new theme has made unacceptable on to
+    /// the next colour and body that are.
+    fn settle_styles(&mut self) {
+        for player in 0..2 {
+            let mut style = self.settings.players[player];
+            let other = self.settings.players[1 - player];
+            let background = self.settings.theme.background;
+            if style.color == other.color || style.color == background {
+                style.color = cycle(&PALETTE, style.color, true, |color| color == other.color || color == background);
+            }
+            if style.head == other.head && style.body == other.body {
+                style.body = cycle(&BODIES, style.body, true, |_| false);
+            }
+            self.settings.players[player] = style;
+        }
+    }
+
+    pub fn countdown_complete(&mut self) -> bool {
+        if self.countdown == 0 {
+            self.countdown = self.update_frequency;
+            true
+        } else {
+            self.countdown -= 1;
+            false
+        }
+    }
+
+    /// While respawning the snake still moves, but waits in place rather
+    /// than dying when something blocks its way.
+    fn resolve_move(&mut self) {
+        let shielded = self.invulnerable > 0;
+        if shielded {
+            self.invulnerable -= 1;
+            self.mark_dirty(self.snake.pos);
+        }
+        let dir = self.snake.dir;
+        let neighbor = self.ahead(self.snake.pos, dir);
+        let other_head = if self.two_player { Some(self.snake2.pos) } else { None };
+        match self.collision(neighbor, Cell::Body, other_head) {
+            None => self.move_to(neighbor, dir),
+            Some(_) if shielded => {}
+            Some(cause) => {
+                self.record_death(0, cause, neighbor);
+                if self.two_player {
+                    self.status = Status::Over2;
+                } else {   
+                    self.lose_life();
+                }
+            }
+        }
+    }
+
+    fn lose_life(&mut self) {
+        self.lives -= 1;
+        if self.lives == 0 {
+            self.status = Status::Over;
+        } else {
+            self.respawn();
+        }
+    }
+
+    fn respawn(&mut self) {
+        for segment in self.snake.body.iter() {
+            let (row, col) = segment.row_col();
+            self.cells[row][col] = Cell::Empty;
+        }
+        let (score, stats) = (self.snake.score, self.snake.stats);
+        self.snake = Snake::new(self.spawn, self.spawn_icon);
+        self.snake.score = score;
+        self.snake.stats = stats;
+        self.invulnerable = RESPAWN_STEPS;
+        self.redraw_all = true;
+        self.follow();
+    }
+
+    /// Pushes `new_body`, the cell the head just left, onto the front of the
+    /// body and drops the tail segment unless the snake is growing. The new
+    /// segment bends from the way the head went to the way back to the old
+    /// neck, and whichever segment ends up last is drawn as the tail.
+    fn update_snake_body(&mut self, second: bool, new_body: Position<WIDTH,HEIGHT>, grow: bool) {
+        let snake = if second { &self.snake2 } else { &self.snake };
+        let behind = snake.body.head().and_then(|neck| self.dir_between(new_body, neck));
+        let glyph = behind.map_or(TAIL_GLYPH, |d| snake.dir.body_glyph(d));
+        let (row, col) = new_body.row_col();
+        self.glyphs[row][col] = glyph as u8;
+        self.mark_dirty(new_body);
+        let snake = if second { &mut self.snake2 } else { &mut self.snake };
+        snake.body.push_head(new_body);
+        let cleared = if grow { None } else { snake.body.pop_tail() };
+        let tail = snake.body.tail();
+        if let Some(cleared) = cleared {
+            let (row, col) = cleared.row_col();
+            self.cells[row][col] = Cell::Empty;
+            self.mark_dirty(cleared);
+        }
+        if let Some(tail) = tail {
+            let (row, col) = tail.row_col();
+            self.glyphs[row][col] = TAIL_GLYPH as u8;
+            self.mark_dirty(tail);
+        }
+    }
+
+    /// The way from `from` to the adjacent cell `to`, across a wrapped edge if need be.
+    fn dir_between(&self, from: Position<WIDTH,HEIGHT>, to: Position<WIDTH,HEIGHT>) -> Option<Dir> {
+        DIRS.into_iter().find(|d| self.ahead(from, *d) == to)
+    }
+
+    fn move_to(&mut self, neighbor: Position<WIDTH,HEIGHT>, dir: Dir) {
+        let curr_pos = self.snake.pos;
+        self.cells[curr_pos.row as usize][curr_pos.col as usize] = Cell::Body;
+        self.snake.pos = neighbor;
+        self.mark_dirty(curr_pos);
+        self.mark_dirty(neighbor);
+        self.snake.dir = dir;
+        let (row, col) = neighbor.row_col();
+        match self.cells[row][col] {
+            Cell::Food => {
+                self.cells[row][col] = Cell::Empty;
+                self.snake.grow(self.total_ticks, self.speed());
+                self.animations.start(Effect::Sparkle, neighbor, self.total_ticks);
+                self.play(Sound::Eat);
+                self.new_food();
+                self.update_snake_body(false, curr_pos, true);
             }
-            self.cells[cleared_pos.row as usize][cleared_pos.col as usize] = Cell::Empty;
+            _ => {self.update_snake_body(false, curr_pos, false);}
+        }
+    }
+
+    /// Keeps the death for the header and statistics and logs it to the serial port.
+    /// A snake leaving the board dies on the last cell it was on.
+    fn record_death(&mut self, player: usize, cause: Cause, pos: Position<WIDTH,HEIGHT>) {
+        let snake = if player == 0 { &mut self.snake } else { &mut self.snake2 };
+        let pos = if cause == Cause::Boundary { snake.pos } else { pos };
+        let death = Death {cause, pos};
+        snake.stats.death = Some(death);
+        let _ = writeln!(Serial, "Player {} died: {}", player + 1, death);
+    }
+
+    fn resolve_move2(&mut self) {
+        let dir = self.snake2.dir;
+        let neighbor = self.ahead(self.snake2.pos, dir);
+        if let Some(cause) = self.collision(neighbor, Cell::Body2, Some(self.snake.pos)) {
+            self.record_death(1, cause, neighbor);
+            self.status = if self.status == Status::Over2 { Status::Draw } else { Status::Over1 };
+        }
+        else if self.status == Status::Normal {
+            self.move_to2(neighbor, dir);
         }
     }
 
@@ -499,48 +1973,102 @@ impl <const WIDTH: usize, const HEIGHT: usize> SnakeGame<WIDTH, HEIGHT> {
         let curr_pos = self.snake2.pos;
         self.cells[curr_pos.row as usize][curr_pos.col as usize] = Cell::Body2;
         self.snake2.pos = neighbor;
+        self.mark_dirty(curr_pos);
+        self.mark_dirty(neighbor);
         self.snake2.dir = dir;
         let (row, col) = neighbor.row_col();
         match self.cells[row][col] {
             Cell::Food => {
                 self.cells[row][col] = Cell::Empty;
-                self.snake2.size += 1;
+                self.snake2.grow(self.total_ticks, self.speed());
+                self.animations.start(Effect::Sparkle, neighbor, self.total_ticks);
+                self.play(Sound::Eat);
                 self.new_food();
-                self.update_snake_body2(curr_pos, true);
+                self.update_snake_body(true, curr_pos, true);
             }
-            _ => {self.update_snake_body2(curr_pos, false);}
+            _ => {self.update_snake_body(true, curr_pos, false);}
         }
     }
 
     fn new_food(&mut self) {
+        let free = self.cell_pos_iter().filter(|p| self.is_free(*p)).count();
+        if free == 0 {
+            self.status = Status::Cleared;
+            return;
+        }
         let mut small_rng = SmallRng::seed_from_u64(self.total_ticks as u64); // https://stackoverflow.com/questions/67627335/how-do-i-use-the-rand-crate-without-the-standard-library
-        let mut row = ((small_rng.next_u32() as f64) / 4294967296.0 * ((HEIGHT-3)as f64) + (1 as f64)) as usize;
-        let mut col = ((small_rng.next_u32() as f64) / 4294967296.0 * ((WIDTH-3)as f64) + (1 as f64)) as usize;
-        while !(self.cells[row][col] == Cell::Empty) {
-            row = ((small_rng.next_u32() as f64) / 4294967296.0 * ((HEIGHT-3)as f64) + (1 as f64)) as usize;
-            col = ((small_rng.next_u32() as f64) / 4294967296.0 * ((WIDTH-3)as f64) + (1 as f64)) as usize;
+        let fair = if self.two_player && self.settings.symmetry != Symmetry::Off { self.fair_food(&mut small_rng) } else { None };
+        let food = fair.or_else(|| {
+            let choice = small_rng.gen_range(0..free);
+            self.cell_pos_iter().filter(|p| self.is_free(*p)).nth(choice)
+        });
+        if let Some(p) = food {
+            let (row, col) = p.row_col();
+            self.cells[row][col] = Cell::Food;
+            self.animations.start(Effect::FadeIn, p, self.total_ticks);
+            self.mark_dirty(p);
         }
-        self.cells[row][col] = Cell::Food;
+    }
+
+    /// Picks among the free cells whose distances from the two heads differ
+    /// the least. Player one's distances go into `steps` first; player two's
+    /// search then overwrites each cell it reaches with `REACHED` plus the
+    /// difference, so one buffer serves both. Distances stay below `REACHED`
+    /// because boards have fewer than 32768 cells.
+    fn fair_food(&self, rng: &mut SmallRng) -> Option<Position<WIDTH,HEIGHT>> {
+        const REACHED: u16 = 0x8000;
+        const SECOND_ONLY: u16 = UNREACHABLE - 1;
+        let passable = |cell: Cell| matches!(cell, Cell::Empty | Cell::Food);
+        let mut steps = [[UNREACHABLE; WIDTH]; HEIGHT];
+        fill_distances(&self.cells, self.snake.pos, passable, &mut steps);
+        search(&self.cells, self.snake2.pos, passable, |p, step| {
+            let (row, col) = p.row_col();
+            let first = steps[row][col];
+            if first >= REACHED && first != UNREACHABLE {
+                return false;
+            }
+            steps[row][col] = if first == UNREACHABLE { SECOND_ONLY } else { REACHED + first.abs_diff(step) };
+            true
+        });
+        let gap = |p: &Position<WIDTH,HEIGHT>| {
+            let (row, col) = p.row_col();
+            let step = steps[row][col];
+            if (REACHED..SECOND_ONLY).contains(&step) { Some(step - REACHED) } else { None }
+        };
+        let best = self.cell_pos_iter().filter(|p| self.is_free(*p)).filter_map(|p| gap(&p)).min()?;
+        let fairest = |p: &Position<WIDTH,HEIGHT>| self.is_free(*p) && gap(p) == Some(best);
+        let choice = rng.gen_range(0..self.cell_pos_iter().filter(fairest).count());
+        self.cell_pos_iter().filter(fairest).nth(choice)
+    }
+
+    /// With lives left to lose, the respawn point stays clear so a new head never lands on food.
+    fn is_free(&self, p: Position<WIDTH,HEIGHT>) -> bool {
+        self.cell(p) == Cell::Empty && p != self.snake.pos && !(self.two_player && p == self.snake2.pos)
+            && !(self.starting_lives > 1 && p == self.spawn)
     }
 
     pub fn status(&self) -> Status {
         self.status
     }
 
+    pub fn speed(&self) -> usize {
+        SLOWEST_UPDATE_FREQUENCY + 1 - self.update_frequency.min(SLOWEST_UPDATE_FREQUENCY)
+    }
+
 }
 
 pub struct RowColIter<const WIDTH: usize, const HEIGHT: usize> {
-    row: usize, col: usize
+    row: usize, col: usize, width: usize, height: usize
 }
 
 impl <const WIDTH: usize, const HEIGHT: usize> Iterator for RowColIter<WIDTH,HEIGHT> {
     type Item = Position<WIDTH,HEIGHT>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.row < HEIGHT {
+        if self.row < self.height.min(HEIGHT) {
             let result = Some(Position {row: self.row as i16, col: self.col as i16});
             self.col += 1;
-            if self.col == WIDTH {
+            if self.col >= self.width.min(WIDTH) {
                 self.col = 0;
                 self.row += 1;
             }
@@ -551,28 +2079,289 @@ impl <const WIDTH: usize, const HEIGHT: usize> Iterator for RowColIter<WIDTH,HEI
     }
 }
 
-fn key2dir2(key: DecodedKey) -> Option<Dir> {
-    match key {
-        DecodedKey::RawKey(k) => match k {
-            KeyCode::ArrowUp => Some(Dir::N),
-            KeyCode::ArrowDown => Some(Dir::S),
-            KeyCode::ArrowLeft => Some(Dir::W),
-            KeyCode::ArrowRight => Some(Dir::E),
-            _ => None
-        }
-        DecodedKey::Unicode(_) => None
+/// Moves the start of a window showing `visible` of `world` cells so that
+/// `target` stays at least a quarter of the window away from either end.
+fn scroll(start: usize, target: usize, visible: usize, world: usize) -> usize {
+    if world <= visible {
+        return 0;
+    }
+    let margin = visible / 4;
+    let start = if target < start + margin {
+        target.saturating_sub(margin)
+    } else if target + margin >= start + visible {
+        target + margin + 1 - visible
+    } else {
+        start
+    };
+    start.min(world - visible)
+}
+
+/// Which of `len` source cells to copy into cell `i` of `target` cells.
+fn stretch(i: usize, target: usize, len: usize) -> usize {
+    if i + 1 == target {
+        len.saturating_sub(1)
+    } else {
+        i.min(len.saturating_sub(2))
     }
 }
 
-fn key2dir(key: DecodedKey) -> Option<Dir> {
-    match key {
-        DecodedKey::RawKey(_) => None,
-        DecodedKey::Unicode(c) => match c {
-            'w' => Some(Dir::N),
-            'a' => Some(Dir::W),
-            's' => Some(Dir::S),
-            'd' => Some(Dir::E),
-            _ => None
+fn num_width(num: usize) -> usize {
+    let mut width = 1;
+    let mut num = num / 10;
+    while num > 0 {
+        width += 1;
+        num /= 10;
+    }
+    width
+}
+
+fn plot_stat(frame: &mut ScreenFrame, label: &str, value: usize, col: usize, row: usize, color: ColorCode) -> usize {
+    let col = frame.plot_str(label, col, row, color);
+    frame.plot_num(value as isize, col + 1, row, color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A solo round on the classic board with the food taken away.
+    fn solo() -> MainGame {
+        round(Settings::default())
+    }
+
+    fn round(settings: Settings) -> MainGame {
+        let mut game = MainGame::blank(Renderer::Text);
+        game.settings = Settings {countdown: 0, ..settings};
+        game.reset();
+        for cell in game.cells.iter_mut().flatten() {
+            if *cell == Cell::Food {
+                *cell = Cell::Empty;
+            }
         }
+        game
     }
-}
\ No newline at end of file
+
+    fn at(row: i16, col: i16) -> Position<WORLD_WIDTH,WORLD_HEIGHT> {
+        Position {row, col}
+    }
+
+    fn count(game: &MainGame, cell: Cell) -> usize {
+        game.cells.iter().flatten().filter(|c| **c == cell).count()
+    }
+
+    fn glyph(game: &MainGame, p: Position<WORLD_WIDTH,WORLD_HEIGHT>) -> char {
+        game.glyphs[p.row as usize][p.col as usize] as char
+    }
+
+    #[test]
+    fn body_follows_the_head_and_keeps_its_length() {
+        let mut game = solo();
+        game.snake = Snake::new(at(10, 10), '>');
+        game.cells[10][11] = Cell::Food;
+        game.cells[10][12] = Cell::Food;
+        game.update();
+        game.update();
+        assert_eq!((game.snake.size, game.snake.body.len()), (2, 2));
+        game.last_key = Some(Dir::S);
+        game.update();
+        assert_eq!(game.snake.pos, at(11, 12));
+        assert_eq!(glyph(&game, at(10, 12)), Dir::S.body_glyph(Dir::W));
+        assert_eq!(glyph(&game, at(10, 11)), TAIL_GLYPH);
+        for _ in 0..4 {
+            game.update();
+            assert_eq!(count(&game, Cell::Body), 2);
+        }
+        assert!(game.snake.body.iter().eq([at(14, 12), at(13, 12)]));
+    }
+
+    #[test]
+    fn fair_food_is_as_far_from_one_head_as_the_other() {
+        let mut game = solo();
+        game.two_player = true;
+        game.snake = Snake::new(at(5, 10), '>');
+        game.snake2 = Snake::new(at(15, 40), '<');
+        let passable = |cell: Cell| matches!(cell, Cell::Empty | Cell::Food);
+        let steps = distances(&game.cells, game.snake.pos, passable);
+        let steps2 = distances(&game.cells, game.snake2.pos, passable);
+        let mut rng = SmallRng::seed_from_u64(7);
+        for _ in 0..10 {
+            let (row, col) = game.fair_food(&mut rng).unwrap().row_col();
+            assert_eq!(steps[row][col], steps2[row][col]);
+        }
+    }
+
+    #[test]
+    fn body_bends_across_a_wrapped_edge() {
+        let mut game = round(Settings {edges: Edges::Wrap, ..Settings::default()});
+        let last = game.dims.width as i16 - 1;
+        game.snake = Snake::new(at(10, last - 1), '>');
+        game.cells[10][last as usize] = Cell::Food;
+        game.cells[10][0] = Cell::Food;
+        game.update();
+        game.update();
+        game.last_key = Some(Dir::S);
+        game.update();
+        assert_eq!(game.snake.pos, at(11, 0));
+        assert_eq!(glyph(&game, at(10, 0)), Dir::S.body_glyph(Dir::W));
+        assert_eq!(glyph(&game, at(10, last)), TAIL_GLYPH);
+        game.update();
+        assert_eq!(game.snake.body.tail(), Some(at(10, 0)));
+        assert_eq!(glyph(&game, at(10, 0)), TAIL_GLYPH);
+        assert_eq!(game.cells[10][last as usize], Cell::Empty);
+    }
+
+    /// Walls over everything but `open` and the snake's head.
+    fn wall_in(game: &mut MainGame, open: &[Position<WORLD_WIDTH,WORLD_HEIGHT>]) {
+        for p in game.cell_pos_iter() {
+            if p != game.snake.pos && !open.contains(&p) {
+                game.cells[p.row as usize][p.col as usize] = Cell::Wall;
+            }
+        }
+    }
+
+    #[test]
+    fn food_only_lands_on_free_cells() {
+        let mut game = solo();
+        game.snake = Snake::new(at(10, 10), '>');
+        let open = [at(10, 11), at(3, 40), at(20, 70)];
+        wall_in(&mut game, &open);
+        game.cells[3][40] = Cell::Body;
+        for tick in 0..50 {
+            game.total_ticks = tick;
+            game.new_food();
+            assert_eq!(count(&game, Cell::Food), 1);
+            let food = game.cell_pos_iter().find(|p| game.cell(*p) == Cell::Food).unwrap();
+            assert!(food == open[0] || food == open[2], "food at {:?}", food);
+            game.cells[food.row as usize][food.col as usize] = Cell::Empty;
+        }
+        assert_eq!(game.status, Status::Normal);
+    }
+
+    #[test]
+    fn eating_the_last_free_cell_clears_the_board() {
+        let mut game = solo();
+        game.snake = Snake::new(at(10, 10), '>');
+        wall_in(&mut game, &[at(10, 11)]);
+        game.cells[10][11] = Cell::Food;
+        game.update();
+        assert_eq!(game.snake.size, 1);
+        assert_eq!(count(&game, Cell::Food), 0);
+        assert_eq!(game.status, Status::Cleared);
+    }
+
+    #[test]
+    fn a_fixed_seed_generates_the_same_board_every_round() {
+        let settings = Settings {level: Level::Generated(Layout::Maze), seed: Some(42), ..Settings::default()};
+        let mut game = round(settings);
+        let walls = game.cells.map(|row| row.map(|cell| cell == Cell::Wall));
+        game.total_ticks += 1000;
+        game.reset();
+        assert!(walls == game.cells.map(|row| row.map(|cell| cell == Cell::Wall)));
+    }
+
+    #[test]
+    fn changing_theme_keeps_chosen_styles_but_settles_clashes() {
+        let mut game = solo();
+        game.settings.players[0] = PlayerStyle {color: Color::Black, head: Head::Ring, body: Body::Beads};
+        game.change(MenuItem::Theme, true);
+        assert_eq!(game.settings.theme, DARK);
+        assert_eq!(game.settings.players[1], DARK.players[1]);
+        let style = game.settings.players[0];
+        assert_eq!((style.head, style.body), (Head::Ring, Body::Beads));
+        assert!(style.color != DARK.background && style.color != DARK.players[1].color);
+        game.change(MenuItem::Theme, false);
+        assert_eq!(game.settings.players[1], CLASSIC.players[1]);
+    }
+
+    #[test]
+    fn players_cannot_pick_the_same_glyphs_or_colour() {
+        let mut game = solo();
+        game.settings.players = [PlayerStyle {color: Color::Blue, head: Head::Arrows, body: Body::Single},
+                                 PlayerStyle {color: Color::Black, head: Head::Arrows, body: Body::Double}];
+        game.setup_row[1] = 1;
+        game.change_style(1, false);
+        assert_eq!(game.settings.players[1].body, Body::Sprite);
+        game.setup_row[1] = 2;
+        game.change_style(1, true);
+        assert_eq!(game.settings.players[1].color, Color::Cyan);
+        game.change_style(1, false);
+        assert_eq!(game.settings.players[1].color, Color::Black);
+    }
+
+    #[test]
+    fn pixel_mode_has_no_rows_setting() {
+        let text = MainGame::blank(Renderer::Text);
+        let pixels = MainGame::blank(Renderer::Pixels);
+        assert!(text.menu().any(|item| item == MenuItem::Rows));
+        assert!(!pixels.menu().any(|item| item == MenuItem::Rows));
+        assert_eq!(pixels.menu().count(), MENU.len() - 1);
+    }
+
+    #[test]
+    fn deaths_are_recorded_with_cause_and_place() {
+        let mut game = solo();
+        game.snake = Snake::new(at(10, 10), '>');
+        wall_in(&mut game, &[]);
+        game.update();
+        let death = game.snake.stats.death.unwrap();
+        assert_eq!((death.cause(), death.pos()), (Cause::Wall, at(10, 11)));
+        let mut line = Line::default();
+        write!(line, "{}", death).unwrap();
+        assert_eq!(&line.bytes[..line.len], b"Wall at row 10, column 11");
+    }
+
+    #[derive(Default)]
+    struct Line {
+        bytes: [u8; 32],
+        len: usize,
+    }
+
+    impl Write for Line {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.bytes[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+            self.len += s.len();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn food_never_lands_on_the_respawn_point() {
+        let mut game = round(Settings {mode: Mode::Lives, ..Settings::default()});
+        game.snake = Snake::new(at(10, 10), '>');
+        let spawn = game.spawn;
+        wall_in(&mut game, &[spawn]);
+        game.new_food();
+        assert_eq!(game.cell(spawn), Cell::Empty);
+        assert_eq!(game.status, Status::Cleared);
+    }
+
+    #[test]
+    fn respawned_snake_moves_but_cannot_die() {
+        let mut game = round(Settings {mode: Mode::Lives, ..Settings::default()});
+        let lives = game.lives;
+        game.snake = Snake::new(at(10, 10), '>');
+        game.cells[10][11] = Cell::Wall;
+        game.update();
+        assert_eq!((game.lives, game.snake.pos, game.invulnerable), (lives - 1, game.spawn, RESPAWN_STEPS));
+        let spawn = game.spawn;
+        let ahead = game.ahead(spawn, game.snake.dir);
+        game.update();
+        assert_eq!(game.snake.pos, ahead);
+        let blocked = game.ahead(ahead, game.snake.dir);
+        game.cells[blocked.row as usize][blocked.col as usize] = Cell::Wall;
+        game.update();
+        assert_eq!((game.lives, game.snake.pos), (lives - 1, ahead));
+        assert_eq!(game.invulnerable, RESPAWN_STEPS - 2);
+    }
+
+    #[test]
+    fn leaving_the_board_dies_on_the_edge_cell() {
+        let mut game = solo();
+        let last = game.dims.width as i16 - 1;
+        game.snake = Snake::new(at(10, last), '>');
+        game.update();
+        let death = game.snake.stats.death.unwrap();
+        assert_eq!((death.cause(), death.pos()), (Cause::Boundary, at(10, last)));
+        assert!(death.pos().is_legal());
+    }
+}
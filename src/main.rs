@@ -3,8 +3,11 @@
 
 use crossbeam::atomic::AtomicCell;
 use pc_keyboard::DecodedKey;
-use pluggable_interrupt_os::{vga_buffer::clear_screen, HandlerTable};
-pub use pluggable_interrupt_template::MainGame;
+use pluggable_interrupt_os::{vga_buffer::{clear_screen, plot_str, Color, ColorCode}, HandlerTable};
+pub use pluggable_interrupt_template::{MainGame, Renderer};
+
+const BOOT_PROMPT: &str = "Hold P for pixel graphics, or press any other key for text";
+const BOOT_TICKS: usize = 3 * 18;
 
 #[no_mangle]
 pub extern "C" fn _start() -> ! {
@@ -20,7 +23,7 @@ static LAST_KEY: AtomicCell<Option<DecodedKey>> = AtomicCell::new(None);
 static TICKED: AtomicCell<bool> = AtomicCell::new(false);
 
 fn cpu_loop() -> ! {
-    let mut kernel = MainGame::new();
+    let mut kernel = MainGame::with_renderer(choose_renderer());
     loop {
         if let Ok(_) = TICKED.compare_exchange(true, false) {
             kernel.tick();
@@ -34,6 +37,22 @@ fn cpu_loop() -> ! {
     }
 }
 
+/// Starts in mode 13h if P is down or pressed within `BOOT_TICKS` of boot,
+/// and in text mode otherwise.
+fn choose_renderer() -> Renderer {
+    plot_str(BOOT_PROMPT, 0, 0, ColorCode::new(Color::White, Color::Black));
+    let mut ticks = 0;
+    while ticks < BOOT_TICKS {
+        if TICKED.compare_exchange(true, false).is_ok() {
+            ticks += 1;
+        }
+        if let Some(k) = LAST_KEY.swap(None) {
+            return if let DecodedKey::Unicode('p' | 'P') = k { Renderer::Pixels } else { Renderer::Text };
+        }
+    }
+    Renderer::Text
+}
+
 fn key(key: DecodedKey) {
     LAST_KEY.store(Some(key));
 }
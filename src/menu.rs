@@ -0,0 +1,205 @@
+use crate::keymap::{Keymap, PLAYER1_KEYS, PLAYER2_KEYS};
+use crate::layout::KeyboardLayout;
+use crate::level_gen::{Layout, Symmetry};
+use crate::theme::{PlayerStyle, Theme, CLASSIC};
+use crate::{Level, World};
+
+pub const MAX_SPEED: usize = 4;
+pub const MAX_COUNTDOWN: usize = 5;
+pub const HIGH_SCORES: usize = 5;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Mode {
+    Solo,
+    Duel,
+    Lives
+}
+
+impl Mode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Solo => "Solo",
+            Mode::Duel => "Duel",
+            Mode::Lives => "Solo+Lives"
+        }
+    }
+
+    pub fn two_player(&self) -> bool {
+        *self == Mode::Duel
+    }
+
+    pub fn index(&self) -> usize {
+        MODES.iter().position(|m| m == self).unwrap_or(0)
+    }
+}
+
+/// What happens at the edge of the board.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Edges {
+    Walls,
+    Wrap
+}
+
+impl Edges {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Edges::Walls => "Walls",
+            Edges::Wrap => "Wrap"
+        }
+    }
+}
+
+pub const MODES: [Mode; 3] = [Mode::Solo, Mode::Duel, Mode::Lives];
+pub const LEVELS: [Level; 4] = [Level::Classic, Level::Generated(Layout::Obstacles), Level::Generated(Layout::Rooms), Level::Generated(Layout::Maze)];
+pub const SYMMETRIES: [Symmetry; 3] = [Symmetry::Off, Symmetry::Point, Symmetry::Mirror];
+pub const EDGES: [Edges; 2] = [Edges::Walls, Edges::Wrap];
+pub const WORLDS: [World; 2] = [World::Screen, World::Large];
+
+/// Everything chosen on the title menu that `reset` reads when a round starts.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Settings {
+    pub mode: Mode,
+    pub level: Level,
+    pub difficulty: usize,
+    pub symmetry: Symmetry,
+    pub speed: usize,
+    pub edges: Edges,
+    pub world: World,
+    pub fog: bool,
+    pub countdown: usize,
+    pub sound: bool,
+    pub theme: Theme,
+    pub players: [PlayerStyle; 2],
+    pub layout: KeyboardLayout,
+    pub keys: [Keymap; 2],
+    /// Generates every level from this seed rather than from the clock.
+    pub seed: Option<u64>
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            mode: Mode::Solo,
+            level: Level::Classic,
+            difficulty: 1,
+            symmetry: Symmetry::Off,
+            speed: MAX_SPEED,
+            edges: Edges::Walls,
+            world: World::Screen,
+            fog: false,
+            countdown: 3,
+            sound: false,
+            theme: CLASSIC,
+            players: CLASSIC.players,
+            layout: KeyboardLayout::Qwerty,
+            keys: [PLAYER1_KEYS, PLAYER2_KEYS],
+            seed: None
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum MenuItem {
+    Play,
+    Mode,
+    Level,
+    Difficulty,
+    Fair,
+    Speed,
+    Edges,
+    World,
+    Fog,
+    Countdown,
+    Sound,
+    Theme,
+    Rows,
+    Keyboard,
+    Players,
+    Controls,
+    HighScores,
+    Quit
+}
+
+impl MenuItem {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MenuItem::Play => "Play",
+            MenuItem::Mode => "Mode",
+            MenuItem::Level => "Level",
+            MenuItem::Difficulty => "Difficulty",
+            MenuItem::Fair => "Fair food",
+            MenuItem::Speed => "Speed",
+            MenuItem::Edges => "Edges",
+            MenuItem::World => "World",
+            MenuItem::Fog => "Fog of war",
+            MenuItem::Countdown => "Countdown",
+            MenuItem::Sound => "Sound",
+            MenuItem::Theme => "Theme",
+            MenuItem::Rows => "Rows",
+            MenuItem::Keyboard => "Keyboard",
+            MenuItem::Players => "Players...",
+            MenuItem::Controls => "Controls...",
+            MenuItem::HighScores => "High scores...",
+            MenuItem::Quit => "Quit"
+        }
+    }
+}
+
+pub const MENU: [MenuItem; 18] = [
+    MenuItem::Play, MenuItem::Mode, MenuItem::Level, MenuItem::Difficulty, MenuItem::Fair, MenuItem::Speed,
+    MenuItem::Edges, MenuItem::World, MenuItem::Fog, MenuItem::Countdown, MenuItem::Sound, MenuItem::Theme,
+    MenuItem::Rows, MenuItem::Keyboard, MenuItem::Players, MenuItem::Controls, MenuItem::HighScores, MenuItem::Quit
+];
+
+/// Steps `value` through `1..=max`, wrapping at both ends.
+pub fn step(value: usize, max: usize, forward: bool) -> usize {
+    if forward { value % max + 1 } else { (value + max - 2) % max + 1 }
+}
+
+/// Steps `value` through `0..=max`, wrapping at both ends.
+pub fn step_from_zero(value: usize, max: usize, forward: bool) -> usize {
+    if forward { (value + 1) % (max + 1) } else { (value + max) % (max + 1) }
+}
+
+/// Inserts `score` into a table kept in descending order, dropping the lowest.
+pub fn record(table: &mut [usize; HIGH_SCORES], score: usize) {
+    if let Some(i) = table.iter().position(|s| score > *s) {
+        table.copy_within(i..HIGH_SCORES - 1, i + 1);
+        table[i] = score;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{record, step, step_from_zero, HIGH_SCORES};
+
+    #[test]
+    fn step_wraps_within_one_to_max() {
+        assert_eq!(step(1, 4, true), 2);
+        assert_eq!(step(4, 4, true), 1);
+        assert_eq!(step(1, 4, false), 4);
+        assert_eq!(step(3, 4, false), 2);
+    }
+
+    #[test]
+    fn step_from_zero_wraps_within_zero_to_max() {
+        assert_eq!(step_from_zero(0, 5, true), 1);
+        assert_eq!(step_from_zero(5, 5, true), 0);
+        assert_eq!(step_from_zero(0, 5, false), 5);
+        assert_eq!(step_from_zero(3, 5, false), 2);
+    }
+
+    #[test]
+    fn record_keeps_the_table_sorted() {
+        let mut table = [0; HIGH_SCORES];
+        for score in [30, 10, 50, 20, 40, 60] {
+            record(&mut table, score);
+        }
+        assert_eq!(table, [60, 50, 40, 30, 20]);
+        record(&mut table, 5);
+        record(&mut table, 20);
+        assert_eq!(table, [60, 50, 40, 30, 20]);
+        record(&mut table, 45);
+        assert_eq!(table, [60, 50, 45, 40, 30]);
+    }
+}
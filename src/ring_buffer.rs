@@ -0,0 +1,191 @@
+use core::iter::{DoubleEndedIterator, ExactSizeIterator, Iterator};
+
+/// Fixed-capacity ring buffer holding up to `WIDTH * HEIGHT` items.
+///
+/// The slots are laid out as a `HEIGHT` x `WIDTH` grid so that a snake body or
+/// search queue can be sized from its board dimensions without
+/// `generic_const_exprs`.
+/// Items are pushed at the head and popped from the tail.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct RingBuffer<T, const WIDTH: usize, const HEIGHT: usize> {
+    slots: [[T; WIDTH]; HEIGHT],
+    tail: usize,
+    len: usize
+}
+
+impl <T: Copy, const WIDTH: usize, const HEIGHT: usize> RingBuffer<T, WIDTH, HEIGHT> {
+    pub fn new(fill: T) -> Self {
+        RingBuffer { slots: [[fill; WIDTH]; HEIGHT], tail: 0, len: 0 }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        WIDTH * HEIGHT
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    pub fn clear(&mut self) {
+        self.tail = 0;
+        self.len = 0;
+    }
+
+    /// Panics if the buffer is already full.
+    pub fn push_head(&mut self, item: T) {
+        assert!(!self.is_full(), "RingBuffer overflow: capacity {}", self.capacity());
+        let index = self.wrap(self.tail + self.len);
+        *self.slot_mut(index) = item;
+        self.len += 1;
+    }
+
+    pub fn pop_tail(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            let item = self.slot(self.tail);
+            self.tail = self.wrap(self.tail + 1);
+            self.len -= 1;
+            Some(item)
+        }
+    }
+
+    pub fn head(&self) -> Option<T> {
+        self.get(0)
+    }
+
+    pub fn tail(&self) -> Option<T> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// Returns the `i`th item counting from the head.
+    pub fn get(&self, i: usize) -> Option<T> {
+        if i < self.len {
+            Some(self.slot(self.wrap(self.tail + self.len - 1 - i)))
+        } else {
+            None
+        }
+    }
+
+    /// Iterates from the head to the tail; call `rev()` to go from the tail to the head.
+    pub fn iter(&self) -> RingBufferIter<'_, T, WIDTH, HEIGHT> {
+        RingBufferIter { buffer: self, front: 0, back: self.len }
+    }
+
+    fn wrap(&self, index: usize) -> usize {
+        index % self.capacity()
+    }
+
+    fn slot(&self, index: usize) -> T {
+        self.slots[index / WIDTH][index % WIDTH]
+    }
+
+    fn slot_mut(&mut self, index: usize) -> &mut T {
+        &mut self.slots[index / WIDTH][index % WIDTH]
+    }
+}
+
+pub struct RingBufferIter<'a, T, const WIDTH: usize, const HEIGHT: usize> {
+    buffer: &'a RingBuffer<T, WIDTH, HEIGHT>,
+    front: usize,
+    back: usize
+}
+
+impl <'a, T: Copy, const WIDTH: usize, const HEIGHT: usize> Iterator for RingBufferIter<'a, T, WIDTH, HEIGHT> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.front += 1;
+            self.buffer.get(self.front - 1)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl <'a, T: Copy, const WIDTH: usize, const HEIGHT: usize> DoubleEndedIterator for RingBufferIter<'a, T, WIDTH, HEIGHT> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.back -= 1;
+            self.buffer.get(self.back)
+        } else {
+            None
+        }
+    }
+}
+
+impl <'a, T: Copy, const WIDTH: usize, const HEIGHT: usize> ExactSizeIterator for RingBufferIter<'a, T, WIDTH, HEIGHT> {}
+
+#[cfg(test)]
+mod tests {
+    use super::RingBuffer;
+
+    #[test]
+    fn capacity_comes_from_dimensions() {
+        let buffer: RingBuffer<u8, 4, 3> = RingBuffer::new(0);
+        assert_eq!(buffer.capacity(), 12);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn pushes_at_head_and_pops_from_tail() {
+        let mut buffer: RingBuffer<u8, 2, 2> = RingBuffer::new(0);
+        for i in 1..=3 {
+            buffer.push_head(i);
+        }
+        assert_eq!(buffer.head(), Some(3));
+        assert_eq!(buffer.tail(), Some(1));
+        assert_eq!(buffer.pop_tail(), Some(1));
+        assert_eq!(buffer.pop_tail(), Some(2));
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(buffer.head(), buffer.tail());
+    }
+
+    #[test]
+    fn wraps_around_when_full() {
+        let mut buffer: RingBuffer<u8, 3, 1> = RingBuffer::new(0);
+        for i in 0..10 {
+            buffer.push_head(i);
+            if buffer.is_full() {
+                buffer.pop_tail();
+            }
+        }
+        assert_eq!(buffer.len(), 2);
+        assert!(buffer.iter().eq([9, 8]));
+        assert!(buffer.iter().rev().eq([8, 9]));
+    }
+
+    #[test]
+    fn pop_from_empty_is_none() {
+        let mut buffer: RingBuffer<u8, 1, 1> = RingBuffer::new(0);
+        assert_eq!(buffer.pop_tail(), None);
+        assert_eq!(buffer.head(), None);
+        assert_eq!(buffer.tail(), None);
+        buffer.push_head(5);
+        buffer.clear();
+        assert!(buffer.iter().next().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_past_capacity_panics() {
+        let mut buffer: RingBuffer<u8, 1, 2> = RingBuffer::new(0);
+        for i in 0..3 {
+            buffer.push_head(i);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use core::fmt;
+
+use crate::text_mode::{inb, outb};
+
+const COM1: u16 = 0x3f8;
+const LINE_STATUS: u16 = COM1 + 5;
+const TRANSMIT_EMPTY: u8 = 0x20;
+
+/// The first serial port as the BIOS left it set up. QEMU shows what is
+/// written here with `-serial stdio`.
+pub struct Serial;
+
+impl fmt::Write for Serial {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.bytes().for_each(send);
+        Ok(())
+    }
+}
+
+fn send(byte: u8) {
+    // Unit tests run as ordinary processes, which may not use I/O ports.
+    if cfg!(test) {
+        return;
+    }
+    unsafe {
+        while inb(LINE_STATUS) & TRANSMIT_EMPTY == 0 {}
+        outb(COM1, byte);
+    }
+}
@@ -0,0 +1,56 @@
+use crate::text_mode::{inb, outb};
+
+const PIT_FREQUENCY: u32 = 1_193_182;
+const PIT_CHANNEL_2: u16 = 0x42;
+const PIT_COMMAND: u16 = 0x43;
+const SPEAKER: u16 = 0x61;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Sound {
+    Eat,
+    Death,
+    Count,
+    Go
+}
+
+impl Sound {
+    pub fn frequency(&self) -> u32 {
+        match self {
+            Sound::Eat => 880,
+            Sound::Death => 110,
+            Sound::Count => 440,
+            Sound::Go => 660
+        }
+    }
+
+    /// How long the tone lasts, in timer ticks.
+    pub fn ticks(&self) -> usize {
+        match self {
+            Sound::Eat => 2,
+            Sound::Death => 12,
+            Sound::Count => 3,
+            Sound::Go => 6
+        }
+    }
+}
+
+/// Starts the PC speaker on a square wave from PIT channel 2.
+pub fn play(frequency: u32) {
+    let divisor = PIT_FREQUENCY / frequency;
+    unsafe {
+        outb(PIT_COMMAND, 0xb6);
+        outb(PIT_CHANNEL_2, divisor as u8);
+        outb(PIT_CHANNEL_2, (divisor >> 8) as u8);
+        let gate = inb(SPEAKER);
+        if gate & 0x03 != 0x03 {
+            outb(SPEAKER, gate | 0x03);
+        }
+    }
+}
+
+pub fn stop() {
+    unsafe {
+        let gate = inb(SPEAKER);
+        outb(SPEAKER, gate & 0xfc);
+    }
+}
@@ -0,0 +1,202 @@
+use core::arch::asm;
+
+use pluggable_interrupt_os::vga_buffer::{ColorCode, BUFFER_WIDTH};
+
+use crate::frame::Screen;
+use crate::graphics::Tile;
+use crate::Dir;
+
+pub const MAX_ROWS: usize = 50;
+pub const GLYPH_ROWS: usize = 16;
+const GLYPH_SLOT: usize = 32;
+const TEXT_BUFFER: *mut u16 = 0xb8000 as *mut u16;
+const FONT_MEMORY: *mut u8 = 0xa0000 as *mut u8;
+pub(crate) const SEQUENCER: u16 = 0x3c4;
+pub(crate) const GRAPHICS: u16 = 0x3ce;
+pub(crate) const CRTC: u16 = 0x3d4;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TextMode {
+    Rows25,
+    Rows50
+}
+
+impl TextMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextMode::Rows25 => "80x25",
+            TextMode::Rows50 => "80x50"
+        }
+    }
+
+    pub fn next(&self) -> TextMode {
+        match self {
+            TextMode::Rows25 => TextMode::Rows50,
+            TextMode::Rows50 => TextMode::Rows25
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        match self {
+            TextMode::Rows25 => 25,
+            TextMode::Rows50 => MAX_ROWS
+        }
+    }
+
+    /// Scan lines per character cell; both modes show 400 lines.
+    pub fn char_height(&self) -> usize {
+        400 / self.rows()
+    }
+}
+
+/// A 256-glyph text-mode font, one byte per row of eight pixels.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Font {
+    glyphs: [[u8; GLYPH_ROWS]; 256]
+}
+
+impl Font {
+    /// Reads the font the VGA card is currently using out of plane 2.
+    pub fn read() -> Font {
+        let mut font = Font { glyphs: [[0; GLYPH_ROWS]; 256] };
+        with_font_plane(|memory| {
+            for (i, glyph) in font.glyphs.iter_mut().enumerate() {
+                for (row, bits) in glyph.iter_mut().enumerate() {
+                    *bits = unsafe { memory.add(i * GLYPH_SLOT + row).read_volatile() };
+                }
+            }
+        });
+        font
+    }
+
+    pub fn load(&self) {
+        with_font_plane(|memory| {
+            for (i, glyph) in self.glyphs.iter().enumerate() {
+                for (row, bits) in glyph.iter().enumerate() {
+                    unsafe { memory.add(i * GLYPH_SLOT + row).write_volatile(*bits) };
+                }
+            }
+        });
+    }
+
+    /// A copy with snake, apple and brick shapes at each `Tile::text_glyph`.
+    pub fn with_snake_glyphs(&self) -> Font {
+        let mut font = *self;
+        for (tile, rows) in SNAKE_GLYPHS.iter() {
+            font.glyphs[tile.text_glyph() as usize] = *rows;
+        }
+        font
+    }
+
+    /// Squeezes every glyph into its top eight rows for 8x8 character cells.
+    /// Each pair of rows is OR-ed together so that one-pixel strokes survive.
+    pub fn halved(&self) -> Font {
+        let mut font = Font { glyphs: [[0; GLYPH_ROWS]; 256] };
+        for (half, glyph) in font.glyphs.iter_mut().zip(self.glyphs.iter()) {
+            for row in 0..GLYPH_ROWS / 2 {
+                half[row] = glyph[2 * row] | glyph[2 * row + 1];
+            }
+        }
+        font
+    }
+}
+
+/// Bodies are four pixels wide and six tall so that straight segments and
+/// corners meet the heads and each other.
+const SNAKE_GLYPHS: [(Tile, [u8; GLYPH_ROWS]); 13] = [
+    (Tile::Head(Dir::N), [0x00, 0x18, 0x3c, 0x7e, 0x7e, 0x5a, 0x7e, 0x7e, 0x7e, 0x3c, 0x3c, 0x3c, 0x3c, 0x3c, 0x3c, 0x3c]),
+    (Tile::Head(Dir::S), [0x3c, 0x3c, 0x3c, 0x3c, 0x3c, 0x3c, 0x3c, 0x7e, 0x7e, 0x7e, 0x5a, 0x7e, 0x7e, 0x3c, 0x18, 0x00]),
+    (Tile::Head(Dir::E), [0x00, 0x00, 0x00, 0x78, 0xfc, 0xfe, 0xf6, 0xff, 0xff, 0xfe, 0xfc, 0x78, 0x00, 0x00, 0x00, 0x00]),
+    (Tile::Head(Dir::W), [0x00, 0x00, 0x00, 0x1e, 0x3f, 0x7f, 0x6f, 0xff, 0xff, 0x7f, 0x3f, 0x1e, 0x00, 0x00, 0x00, 0x00]),
+    (Tile::Body(0b0011), [0x3c; GLYPH_ROWS]),
+    (Tile::Body(0b1100), [0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (Tile::Body(0b0110), [0x00, 0x00, 0x00, 0x00, 0x00, 0x0f, 0x1f, 0x3f, 0x3f, 0x3f, 0x3f, 0x3c, 0x3c, 0x3c, 0x3c, 0x3c]),
+    (Tile::Body(0b1010), [0x00, 0x00, 0x00, 0x00, 0x00, 0xf0, 0xf8, 0xfc, 0xfc, 0xfc, 0xfc, 0x3c, 0x3c, 0x3c, 0x3c, 0x3c]),
+    (Tile::Body(0b0101), [0x3c, 0x3c, 0x3c, 0x3c, 0x3c, 0x3f, 0x3f, 0x3f, 0x3f, 0x1f, 0x0f, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (Tile::Body(0b1001), [0x3c, 0x3c, 0x3c, 0x3c, 0x3c, 0xfc, 0xfc, 0xfc, 0xfc, 0xf8, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (Tile::Body(0), [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x3c, 0x3c, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    (Tile::Wall, [0x00, 0xf7, 0xf7, 0xf7, 0x00, 0x7f, 0x7f, 0x7f, 0x00, 0xf7, 0xf7, 0xf7, 0x00, 0x7f, 0x7f, 0x7f]),
+    (Tile::Food, [0x00, 0x08, 0x10, 0x36, 0x7e, 0x7e, 0x7e, 0x7e, 0x7e, 0x3c, 0x3c, 0x18, 0x00, 0x00, 0x00, 0x00])
+];
+
+/// Reprograms the CRT controller for `mode`'s character height and loads
+/// `font`, which must be the full-height 8x16 font.
+pub fn set_text_mode(mode: TextMode, font: &Font) {
+    match mode {
+        TextMode::Rows25 => font.load(),
+        TextMode::Rows50 => font.halved().load()
+    }
+    let height = mode.char_height() as u8;
+    unsafe {
+        let max_scan_line = read_register(CRTC, 0x09);
+        write_register(CRTC, 0x09, (max_scan_line & 0xe0) | (height - 1));
+        let cursor_start = read_register(CRTC, 0x0a);
+        write_register(CRTC, 0x0a, (cursor_start & 0xe0) | (height - 3));
+        let cursor_end = read_register(CRTC, 0x0b);
+        write_register(CRTC, 0x0b, (cursor_end & 0xe0) | (height - 2));
+    }
+}
+
+pub struct TextScreen;
+
+impl Screen for TextScreen {
+    fn write_cell(&mut self, c: char, col: usize, row: usize, color: ColorCode) {
+        write_cell(c, col, row, color);
+    }
+}
+
+/// Writes straight to the text buffer, which unlike `vga_buffer::plot` can
+/// reach the rows below 25 in 80x50 mode.
+pub fn write_cell(c: char, col: usize, row: usize, color: ColorCode) {
+    if col < BUFFER_WIDTH && row < MAX_ROWS {
+        let value = (attribute(color) as u16) << 8 | c as u8 as u16;
+        unsafe { TEXT_BUFFER.add(row * BUFFER_WIDTH + col).write_volatile(value) };
+    }
+}
+
+/// The attribute byte: background in the high nibble, foreground in the low.
+pub fn attribute(color: ColorCode) -> u8 {
+    // ColorCode is a transparent wrapper around the attribute byte.
+    unsafe { core::mem::transmute(color) }
+}
+
+/// Maps plane 2, where the character generator keeps its font, at 0xa0000
+/// for the duration of `f`, then restores the text-mode memory layout.
+fn with_font_plane<R>(f: impl FnOnce(*mut u8) -> R) -> R {
+    unsafe {
+        let saved = [read_register(SEQUENCER, 2), read_register(SEQUENCER, 4),
+                     read_register(GRAPHICS, 4), read_register(GRAPHICS, 5), read_register(GRAPHICS, 6)];
+        write_register(SEQUENCER, 2, 0x04);
+        write_register(SEQUENCER, 4, 0x07);
+        write_register(GRAPHICS, 4, 0x02);
+        write_register(GRAPHICS, 5, 0x00);
+        write_register(GRAPHICS, 6, 0x04);
+        let result = f(FONT_MEMORY);
+        write_register(SEQUENCER, 2, saved[0]);
+        write_register(SEQUENCER, 4, saved[1]);
+        write_register(GRAPHICS, 4, saved[2]);
+        write_register(GRAPHICS, 5, saved[3]);
+        write_register(GRAPHICS, 6, saved[4]);
+        result
+    }
+}
+
+pub(crate) unsafe fn read_register(port: u16, index: u8) -> u8 {
+    outb(port, index);
+    inb(port + 1)
+}
+
+pub(crate) unsafe fn write_register(port: u16, index: u8, value: u8) {
+    outb(port, index);
+    outb(port + 1, value);
+}
+
+pub(crate) unsafe fn outb(port: u16, value: u8) {
+    asm!("out dx, al", in("dx") port, in("al") value, options(nomem, nostack, preserves_flags));
+}
+
+pub(crate) unsafe fn inb(port: u16) -> u8 {
+    let value: u8;
+    asm!("in al, dx", out("al") value, in("dx") port, options(nomem, nostack, preserves_flags));
+    value
+}
@@ -0,0 +1,225 @@
+use pluggable_interrupt_os::vga_buffer::{Color, ColorCode};
+
+use crate::graphics::Tile;
+use crate::Dir;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Head {
+    Sprite,
+    Arrows,
+    Triangles,
+    Face,
+    SolidFace,
+    Ring
+}
+
+impl Head {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Head::Sprite => "Sprite",
+            Head::Arrows => "Arrows",
+            Head::Triangles => "Triangle",
+            Head::Face => "Face",
+            Head::SolidFace => "Solid",
+            Head::Ring => "Ring"
+        }
+    }
+
+    pub fn glyph(&self, dir: Dir) -> char {
+        match (self, dir) {
+            (Head::Sprite, _) => Tile::Head(dir).text_glyph(),
+            (Head::Arrows, _) => dir.icon(),
+            (Head::Triangles, Dir::N) => '\u{1E}',
+            (Head::Triangles, Dir::S) => '\u{1F}',
+            (Head::Triangles, Dir::E) => '\u{10}',
+            (Head::Triangles, Dir::W) => '\u{11}',
+            (Head::Face, _) => '\u{01}',
+            (Head::SolidFace, _) => '\u{02}',
+            (Head::Ring, _) => 'O'
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Body {
+    Sprite,
+    Single,
+    Double,
+    Block,
+    Shaded,
+    Beads
+}
+
+impl Body {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Body::Sprite => "Sprite",
+            Body::Single => "Single",
+            Body::Double => "Double",
+            Body::Block => "Block",
+            Body::Shaded => "Shaded",
+            Body::Beads => "Beads"
+        }
+    }
+}
+
+pub const HEADS: [Head; 6] = [Head::Sprite, Head::Arrows, Head::Triangles, Head::Face, Head::SolidFace, Head::Ring];
+pub const BODIES: [Body; 6] = [Body::Sprite, Body::Single, Body::Double, Body::Block, Body::Shaded, Body::Beads];
+pub const PALETTE: [Color; 16] = [
+    Color::Black, Color::Blue, Color::Green, Color::Cyan, Color::Red, Color::Magenta, Color::Brown, Color::LightGray,
+    Color::DarkGray, Color::LightBlue, Color::LightGreen, Color::LightCyan, Color::LightRed, Color::Pink, Color::Yellow, Color::White
+];
+
+pub fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::Black => "Black",
+        Color::Blue => "Blue",
+        Color::Green => "Green",
+        Color::Cyan => "Cyan",
+        Color::Red => "Red",
+        Color::Magenta => "Magenta",
+        Color::Brown => "Brown",
+        Color::LightGray => "Lt Gray",
+        Color::DarkGray => "Dk Gray",
+        Color::LightBlue => "Lt Blue",
+        Color::LightGreen => "Lt Green",
+        Color::LightCyan => "Lt Cyan",
+        Color::LightRed => "Lt Red",
+        Color::Pink => "Pink",
+        Color::Yellow => "Yellow",
+        Color::White => "White"
+    }
+}
+
+/// Steps through `options` from `current`, skipping every option for which
+/// `rejected` holds. Returns `current` if nothing else is acceptable.
+pub fn cycle<T: Copy + PartialEq>(options: &[T], current: T, forward: bool, rejected: impl Fn(T) -> bool) -> T {
+    let start = options.iter().position(|o| *o == current).unwrap_or(0);
+    let len = options.len();
+    (1..len)
+        .map(|step| options[if forward { (start + step) % len } else { (start + len - step) % len }])
+        .find(|o| !rejected(*o))
+        .unwrap_or(current)
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct PlayerStyle {
+    pub color: Color,
+    pub head: Head,
+    pub body: Body
+}
+
+impl PlayerStyle {
+    pub fn head(&self, dir: Dir) -> char {
+        self.head.glyph(dir)
+    }
+
+    /// Converts a single-line body glyph to this player's body style.
+    pub fn body(&self, glyph: char) -> char {
+        let tail = glyph == '\u{F9}';
+        match self.body {
+            Body::Sprite => Tile::body(glyph).text_glyph(),
+            Body::Single => glyph,
+            Body::Double => match glyph {
+                '\u{B3}' => '\u{BA}',
+                '\u{C4}' => '\u{CD}',
+                '\u{DA}' => '\u{C9}',
+                '\u{BF}' => '\u{BB}',
+                '\u{C0}' => '\u{C8}',
+                '\u{D9}' => '\u{BC}',
+                '\u{F9}' => '\u{FE}',
+                _ => glyph
+            },
+            Body::Block => if tail { '\u{B2}' } else { '\u{DB}' },
+            Body::Shaded => if tail { '\u{B0}' } else { '\u{B1}' },
+            Body::Beads => if tail { '.' } else { 'o' }
+        }
+    }
+}
+
+/// Every colour and glyph the game draws with. `players` are the styles the
+/// theme suggests; the ones in play live in `Settings`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: Color,
+    pub text: Color,
+    pub highlight: Color,
+    pub players: [PlayerStyle; 2],
+    pub wall: (char, Color),
+    pub food: (char, Color),
+    pub collision: Color,
+    pub panel: Color,
+    pub panel_text: Color
+}
+
+impl Theme {
+    pub fn header(&self, foreground: Color) -> ColorCode {
+        ColorCode::new(foreground, self.background)
+    }
+
+    pub fn on_panel(&self, foreground: Color) -> ColorCode {
+        ColorCode::new(foreground, self.panel)
+    }
+}
+
+pub const CLASSIC: Theme = Theme {
+    name: "Classic",
+    background: Color::Green,
+    text: Color::White,
+    highlight: Color::Yellow,
+    players: [PlayerStyle { color: Color::Blue, head: Head::Sprite, body: Body::Sprite },
+              PlayerStyle { color: Color::Magenta, head: Head::Sprite, body: Body::Double }],
+    wall: (Tile::Wall.text_glyph(), Color::Brown),
+    food: (Tile::Food.text_glyph(), Color::Red),
+    collision: Color::Red,
+    panel: Color::LightGray,
+    panel_text: Color::Black
+};
+
+pub const DARK: Theme = Theme {
+    name: "Dark",
+    background: Color::Black,
+    text: Color::LightGray,
+    highlight: Color::Yellow,
+    players: [PlayerStyle { color: Color::LightCyan, head: Head::Arrows, body: Body::Single },
+              PlayerStyle { color: Color::Pink, head: Head::Arrows, body: Body::Double }],
+    wall: ('\u{B1}', Color::DarkGray),
+    food: ('@', Color::LightGreen),
+    collision: Color::Red,
+    panel: Color::DarkGray,
+    panel_text: Color::White
+};
+
+pub const HIGH_CONTRAST: Theme = Theme {
+    name: "High Contrast",
+    background: Color::Black,
+    text: Color::White,
+    highlight: Color::Yellow,
+    players: [PlayerStyle { color: Color::White, head: Head::Arrows, body: Body::Single },
+              PlayerStyle { color: Color::Yellow, head: Head::Arrows, body: Body::Double }],
+    wall: ('\u{DB}', Color::LightGray),
+    food: ('\u{04}', Color::LightGreen),
+    collision: Color::LightRed,
+    panel: Color::Blue,
+    panel_text: Color::White
+};
+
+/// Blue against yellow stays distinct under the common forms of colour
+/// blindness, and the second player's double-line body does not rely on
+/// colour at all.
+pub const COLOR_BLIND: Theme = Theme {
+    name: "Colour-blind",
+    background: Color::Black,
+    text: Color::White,
+    highlight: Color::LightCyan,
+    players: [PlayerStyle { color: Color::LightBlue, head: Head::Arrows, body: Body::Single },
+              PlayerStyle { color: Color::Yellow, head: Head::Arrows, body: Body::Double }],
+    wall: ('\u{B1}', Color::LightGray),
+    food: ('\u{04}', Color::White),
+    collision: Color::Magenta,
+    panel: Color::DarkGray,
+    panel_text: Color::White
+};
+
+pub const THEMES: [Theme; 4] = [CLASSIC, DARK, HIGH_CONTRAST, COLOR_BLIND];